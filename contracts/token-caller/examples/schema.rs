@@ -1,10 +1,11 @@
 use cosmwasm_schema::write_api;
-use token_caller::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use token_caller::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
-        query: QueryMsg
+        query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
@@ -1,10 +1,11 @@
 use cosmwasm_std::{entry_point, to_binary, Binary, Deps, StdResult, Uint128};
 use cosmwasm_std::{DepsMut, Env, MessageInfo, Response};
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
 
 #[cfg(not(feature = "library"))]
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, OnFTReceivedResponse, QueryMsg};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, OnFTReceivedResponse, QueryMsg};
 use token_standard::ExecuteMsg as TokenExecuteMsg;
 
 // version info for migration info
@@ -56,7 +57,13 @@ pub fn execute(
             amount,
             current_allowance,
         ),
-        ExecuteMsg::Receive { sender, amount } => exec::receive(deps, env, info, sender, amount),
+        ExecuteMsg::Receive {
+            token,
+            from,
+            operator,
+            amount,
+            msg,
+        } => exec::receive(deps, env, info, token, from, operator, amount, msg),
     }
 }
 
@@ -79,7 +86,11 @@ mod exec {
 
         let sub_msg = WasmMsg::Execute {
             contract_addr: contract,
-            msg: to_binary(&TokenExecuteMsg::Transfer { recipient, amount })?,
+            msg: to_binary(&TokenExecuteMsg::Transfer {
+                recipient,
+                amount,
+                on_receive_error: None,
+            })?,
             funds: vec![],
         };
 
@@ -106,6 +117,7 @@ mod exec {
                 owner,
                 recipient,
                 amount,
+                on_receive_error: None,
             })?,
             funds: vec![],
         };
@@ -132,6 +144,7 @@ mod exec {
                 spender,
                 amount,
                 current_allowance,
+                expires: None,
             })?,
             funds: vec![],
         };
@@ -139,15 +152,19 @@ mod exec {
         Ok(Response::new().add_submessage(SubMsg::new(sub_msg)))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn receive(
         deps: DepsMut,
         _env: Env,
         info: MessageInfo,
-        sender: String,
+        token: String,
+        _from: String,
+        _operator: String,
         _amount: Uint128,
+        _msg: Option<Binary>,
     ) -> Result<Response, ContractError> {
-        // check sender is real sender and contract.
-        if info.sender != sender || !is_contract(deps.as_ref(), &sender) {
+        // check the hook is called by the token contract itself.
+        if info.sender != token || !is_contract(deps.as_ref(), &token) {
             return Err(ContractError::Unauthorized {});
         }
 
@@ -164,23 +181,55 @@ fn is_contract(deps: Deps<'_>, recipient: &str) -> bool {
         .is_ok()
 }
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: stored.contract,
+        });
+    }
+
+    // refuse downgrades
+    let stored_version: Version = stored.version.parse()?;
+    let version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version > version {
+        return Err(ContractError::CannotMigrateVersion {
+            previous_version: stored.version,
+        });
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::OnFTReceived {
-            sender,
-            owner,
+            token,
+            from,
+            operator,
             amount,
-        } => to_binary(&query_on_ft_received(deps, sender, owner, amount)?),
+            msg,
+        } => to_binary(&query_on_ft_received(
+            deps, token, from, operator, amount, msg,
+        )?),
     }
 }
 
 // OnFTReceived
 pub fn query_on_ft_received(
     _deps: Deps,
-    _sender: String,
-    _owner: String,
+    _token: String,
+    _from: String,
+    _operator: String,
     amount: Uint128,
+    _msg: Option<Binary>,
 ) -> StdResult<OnFTReceivedResponse> {
     if amount == Uint128::zero() {
         return Ok(OnFTReceivedResponse { enable: false });
@@ -229,6 +278,7 @@ mod tests {
         let expected = TokenExecuteMsg::Transfer {
             recipient,
             amount: transfer_amount,
+            on_receive_error: None,
         };
         assert_eq!(
             &res.messages[0],
@@ -239,4 +289,44 @@ mod tests {
             })
         )
     }
+
+    #[test]
+    fn migrate() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        do_instantiate(deps.as_mut(), "creator");
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        super::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+
+        // refuse downgrade
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err = super::migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrateVersion {
+                previous_version: "99.0.0".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn receive() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let token = String::from("token");
+        do_instantiate(deps.as_mut(), "creator");
+
+        // the hook must come from the token it names
+        let msg = ExecuteMsg::Receive {
+            token: token.clone(),
+            from: String::from("owner"),
+            operator: String::from("owner"),
+            amount: Uint128::new(100),
+            msg: None,
+        };
+        let info = mock_info("impostor", &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
 }
@@ -1,9 +1,12 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Binary, Uint128};
 
 #[cw_serde]
 pub struct InstantiateMsg {}
 
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum ExecuteMsg {
     Transfer {
@@ -24,8 +27,11 @@ pub enum ExecuteMsg {
         current_allowance: Uint128,
     },
     Receive {
-        sender: String,
+        token: String,
+        from: String,
+        operator: String,
         amount: Uint128,
+        msg: Option<Binary>,
     },
 }
 
@@ -34,9 +40,11 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     #[returns(OnFTReceivedResponse)]
     OnFTReceived {
-        sender: String,
-        owner: String,
+        token: String,
+        from: String,
+        operator: String,
         amount: Uint128,
+        msg: Option<Binary>,
     },
 }
 
@@ -1,10 +1,11 @@
 use cosmwasm_schema::write_api;
-use token_standard::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
+use token_standard::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
 
 fn main() {
     write_api! {
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
-        query: QueryMsg
+        query: QueryMsg,
+        migrate: MigrateMsg,
     }
 }
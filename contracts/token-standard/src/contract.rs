@@ -1,27 +1,52 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
-    Storage, Uint128,
+    coins, to_binary, Addr, BankMsg, Binary, BlockInfo, Deps, DepsMut, Empty, Env, Event,
+    MessageInfo, Order, Reply, Response, StdError, StdResult, Storage, SubMsgResult, Uint128,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{DownloadLogoResponse, EmbeddedLogo, Logo, LogoInfo, MarketingInfoResponse};
+use cw_storage_plus::Bound;
+use cw_utils::{may_pay, must_pay, Expiration};
+use semver::Version;
 
 use crate::error::ContractError;
-use crate::event::{approval_event, transfer_event};
+use crate::event::{
+    approval_event, burn_event, delegate_changed_event, deposit_event, mint_event, transfer_event,
+    transfer_reverted_event, withdraw_event,
+};
+use crate::migrations;
 use crate::msg::{
-    AllowanceResponse, BalanceResponse, ExecuteMsg, InfoResponse, InstantiateMsg,
-    OnFTReceivedResponse, QueryMsg, TotalSupplyResponse,
+    AccountBalance, AllAccountsResponse, AllAllowancesResponse, AllSpenderAllowancesResponse,
+    AllowanceInfo, AllowanceResponse, AllowlistResponse, BalanceResponse, DelegatesResponse,
+    ExecuteMsg, FrozenAccountsResponse, InfoResponse, InitialBalances, InstantiateMsg, MigrateMsg,
+    MinterResponse, NonceResponse, OnFTReceivedResponse, OnReceiveError, PermitSignDoc, QueryMsg,
+    ReceiverPolicy, RelayedTransferSignDoc, ReserveResponse, SpenderAllowanceInfo, StatusResponse,
+    TotalSupplyResponse, TransferItem, VestingResponse, VestingSchedule, VotingPowerResponse,
+};
+use crate::ownership;
+use crate::signature;
+use crate::state::{
+    Lockup, MinterData, PendingReceive, TokenInfo, ALLOWANCES, ALLOWANCES_SPENDER, ALLOWLIST,
+    BALANCES, COMPLIANCE, DELEGATES, FROZEN, LOCKUPS, LOGO, MARKETING_INFO, NEXT_REPLY_ID, PAUSED,
+    PAUSER, PENDING_RECEIVES, TOKEN_INFO, TOTAL_SUPPLY, VOTING_POWER,
 };
-use crate::state::{TokenInfo, ALLOWANCES, ALLOWANCES_SPENDER, BALANCES, TOKEN_INFO};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:token-standard";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// embedded logos are limited to 5KB
+const LOGO_SIZE_CAP: usize = 5 * 1024;
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
-    deps: DepsMut,
-    _env: Env,
+    mut deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
@@ -29,22 +54,100 @@ pub fn instantiate(
     // check valid token info
     msg.validate()?;
 
-    let address = info.sender;
-    BALANCES.save(deps.storage, &address, &msg.initial_balances)?;
-    let total_supply = msg.initial_balances;
+    let total_supply = msg.initial_balances.total()?;
+
+    let mint = match msg.mint {
+        Some(m) => Some(MinterData {
+            minter: deps.api.addr_validate(&m.minter)?,
+            cap: m.cap,
+        }),
+        None => None,
+    };
 
     let data = TokenInfo {
         name: msg.name,
         symbol: msg.symbol,
         decimals: msg.decimals,
-        total_supply,
+        mint,
+        receiver_policy: msg.receiver_policy.unwrap_or_default(),
+        allowlist_only: msg.allowlist.is_some(),
+        native_denom: msg.native_denom,
     };
+    // a wrapped native supply is backed by the coins sent along from the start
+    if let Some(denom) = &data.native_denom {
+        if may_pay(&info, denom)? != total_supply {
+            return Err(ContractError::InitialSupplyNotFunded {
+                expected: total_supply,
+            });
+        }
+    }
     TOKEN_INFO.save(deps.storage, &data)?;
+    TOTAL_SUPPLY.save(deps.storage, &total_supply, env.block.height)?;
+    // the allowlist applies to the genesis balances too
+    for address in msg.allowlist.unwrap_or_default() {
+        let addr = deps.api.addr_validate(&address)?;
+        ALLOWLIST.save(deps.storage, &addr, &Empty {})?;
+    }
+    let events = create_accounts(&mut deps, &env.block, &info.sender, msg.initial_balances)?;
+    ownership::initialize_owner(deps.storage, Some(info.sender.clone()))?;
+    PAUSER.save(deps.storage, &info.sender)?;
+    COMPLIANCE.save(deps.storage, &info.sender)?;
+
+    if let Some(marketing) = msg.marketing {
+        let logo = match marketing.logo {
+            Some(logo) => {
+                exec::verify_logo(&logo)?;
+                LOGO.save(deps.storage, &logo)?;
+                Some(exec::logo_info(logo))
+            }
+            None => None,
+        };
+        let data = MarketingInfoResponse {
+            project: marketing.project,
+            description: marketing.description,
+            marketing: marketing
+                .marketing
+                .map(|addr| deps.api.addr_validate(&addr))
+                .transpose()?,
+            logo,
+        };
+        MARKETING_INFO.save(deps.storage, &data)?;
+    }
 
-    Ok(Response::default())
+    Ok(Response::default().add_events(events))
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
+// credits the genesis balances and returns a Mint event per allocation
+fn create_accounts(
+    deps: &mut DepsMut,
+    block: &BlockInfo,
+    creator: &Addr,
+    initial_balances: InitialBalances,
+) -> Result<Vec<Event>, ContractError> {
+    let accounts = match initial_balances {
+        InitialBalances::Amount(amount) => vec![(creator.clone(), amount)],
+        InitialBalances::Accounts(accounts) => accounts
+            .into_iter()
+            .map(|account| Ok((deps.api.addr_validate(&account.address)?, account.amount)))
+            .collect::<StdResult<Vec<_>>>()?,
+    };
+
+    let mut addresses: Vec<&Addr> = accounts.iter().map(|(address, _)| address).collect();
+    addresses.sort();
+    addresses.dedup();
+    if addresses.len() != accounts.len() {
+        return Err(ContractError::DuplicateInitialBalanceAddresses {});
+    }
+
+    let mut events = Vec::with_capacity(accounts.len());
+    for (address, amount) in accounts {
+        exec::_add_balance(deps.storage, block, &address, amount)?;
+        events.push(mint_event(creator.as_ref(), address.as_ref(), amount));
+    }
+    Ok(events)
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "cw20-compat")), entry_point)]
 pub fn execute(
     deps: DepsMut,
     env: Env,
@@ -52,24 +155,125 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::Transfer { recipient, amount } => {
-            exec::transfer(deps, env, info, recipient, amount)
-        }
+        ExecuteMsg::Transfer {
+            recipient,
+            amount,
+            on_receive_error,
+        } => exec::transfer(deps, env, info, recipient, amount, on_receive_error),
         ExecuteMsg::TransferFrom {
             owner,
             recipient,
             amount,
-        } => exec::transfer_from(deps, env, info, owner, recipient, amount),
+            on_receive_error,
+        } => exec::transfer_from(deps, env, info, owner, recipient, amount, on_receive_error),
         ExecuteMsg::Approve {
             spender,
             amount,
             current_allowance,
-        } => exec::approve(deps, env, info, spender, amount, current_allowance),
-        ExecuteMsg::Receive { sender, amount } => exec::receive(deps, env, info, sender, amount),
+            expires,
+        } => exec::approve(deps, env, info, spender, amount, current_allowance, expires),
+        ExecuteMsg::IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => exec::increase_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => exec::decrease_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::BatchTransfer {
+            transfers,
+            on_receive_error,
+        } => exec::batch_transfer(deps, env, info, transfers, on_receive_error),
+        ExecuteMsg::BatchTransferFrom {
+            owner,
+            transfers,
+            on_receive_error,
+        } => exec::batch_transfer_from(deps, env, info, owner, transfers, on_receive_error),
+        ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+            on_receive_error,
+        } => exec::send(deps, env, info, contract, amount, msg, on_receive_error),
+        ExecuteMsg::Receive {
+            token,
+            from,
+            operator,
+            amount,
+            msg,
+        } => exec::receive(deps, env, info, token, from, operator, amount, msg),
+        ExecuteMsg::Mint { recipient, amount } => exec::mint(deps, env, info, recipient, amount),
+        ExecuteMsg::UpdateMinter { new_minter } => exec::update_minter(deps, env, info, new_minter),
+        ExecuteMsg::UpdateReceiverPolicy { policy } => {
+            exec::update_receiver_policy(deps, env, info, policy)
+        }
+        ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => exec::update_marketing(deps, env, info, project, description, marketing),
+        ExecuteMsg::UploadLogo(logo) => exec::upload_logo(deps, env, info, logo),
+        ExecuteMsg::TransferOwnership { new_owner, expiry } => {
+            ownership::transfer_ownership(deps, env, info, new_owner, expiry)
+        }
+        ExecuteMsg::AcceptOwnership {} => ownership::accept_ownership(deps, env, info),
+        ExecuteMsg::RenounceOwnership {} => ownership::renounce_ownership(deps, env, info),
+        ExecuteMsg::Pause {} => exec::pause(deps, env, info),
+        ExecuteMsg::Unpause {} => exec::unpause(deps, env, info),
+        ExecuteMsg::UpdatePauser { new_pauser } => exec::update_pauser(deps, env, info, new_pauser),
+        ExecuteMsg::Freeze { address } => exec::freeze(deps, env, info, address),
+        ExecuteMsg::Unfreeze { address } => exec::unfreeze(deps, env, info, address),
+        ExecuteMsg::UpdateCompliance { new_compliance } => {
+            exec::update_compliance(deps, env, info, new_compliance)
+        }
+        ExecuteMsg::AddToAllowlist { address } => exec::add_to_allowlist(deps, env, info, address),
+        ExecuteMsg::RemoveFromAllowlist { address } => {
+            exec::remove_from_allowlist(deps, env, info, address)
+        }
+        ExecuteMsg::UpdateAllowlistMode { allowlist_only } => {
+            exec::update_allowlist_mode(deps, env, info, allowlist_only)
+        }
+        ExecuteMsg::Delegate { delegatee } => exec::delegate(deps, env, info, delegatee),
+        ExecuteMsg::Permit {
+            owner,
+            spender,
+            amount,
+            expires,
+            nonce,
+            pubkey,
+            signature,
+        } => exec::permit(
+            deps, env, info, owner, spender, amount, expires, nonce, pubkey, signature,
+        ),
+        ExecuteMsg::RelayedTransfer {
+            from,
+            recipient,
+            amount,
+            fee,
+            nonce,
+            deadline,
+            relayer,
+            pubkey,
+            signature,
+        } => exec::relayed_transfer(
+            deps, env, info, from, recipient, amount, fee, nonce, deadline, relayer, pubkey,
+            signature,
+        ),
+        ExecuteMsg::Deposit {} => exec::deposit(deps, env, info),
+        ExecuteMsg::Withdraw { amount } => exec::withdraw(deps, env, info, amount),
+        ExecuteMsg::CreateLockup {
+            address,
+            amount,
+            schedule,
+        } => exec::create_lockup(deps, env, info, address, amount, schedule),
+        ExecuteMsg::Burn { amount } => exec::burn(deps, env, info, amount),
+        ExecuteMsg::BurnFrom { owner, amount } => exec::burn_from(deps, env, info, owner, amount),
     }
 }
 
-mod exec {
+pub(crate) mod exec {
     use cosmwasm_std::{SubMsg, WasmMsg};
 
     use super::*;
@@ -80,370 +284,3846 @@ mod exec {
         info: MessageInfo,
         recipient: String,
         amount: Uint128,
+        on_receive_error: Option<OnReceiveError>,
     ) -> Result<Response, ContractError> {
         if amount == Uint128::zero() {
             return Err(ContractError::InvalidZeroAmount {});
         }
 
-        let rcpt_addr = deps.api.addr_validate(&recipient)?;
-
-        BALANCES.update(
-            deps.storage,
+        _move_and_notify(
+            deps,
+            &env,
             &info.sender,
-            |balance: Option<Uint128>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default().checked_sub(amount)?)
-            },
-        )?;
-        BALANCES.update(
-            deps.storage,
-            &rcpt_addr,
-            |balance: Option<Uint128>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default().checked_add(amount)?)
-            },
-        )?;
-
-        let mut res = Response::default().add_event(transfer_event(
-            info.sender.as_ref(),
-            recipient.as_ref(),
+            &info.sender,
+            recipient,
             amount,
-        ));
-
-        // if recipient is smart contract
-        if is_contract(deps.as_ref(), &recipient) {
-            // check if define onReceived
-            let rr = can_receive(deps, &recipient, &env, info, amount)?;
-            if !rr.enable {
-                return Err(ContractError::NonTransferable {});
-            }
-
-            let sub_msg = WasmMsg::Execute {
-                contract_addr: recipient,
-                msg: to_binary(&ExecuteMsg::Receive {
-                    sender: env.contract.address.into(),
-                    amount,
-                })?,
-                funds: vec![],
-            };
-            res = res
-                .add_submessage(SubMsg::new(sub_msg))
-                .add_attribute("on_ft_received", rr.enable.to_string());
-        };
-
-        Ok(res)
+            None,
+            on_receive_error.unwrap_or_default(),
+        )
     }
 
-    fn can_receive(
-        deps: DepsMut<'_>,
-        recipient: &String,
-        env: &Env,
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer_from(
+        deps: DepsMut,
+        env: Env,
         info: MessageInfo,
+        owner: String,
+        recipient: String,
         amount: Uint128,
-    ) -> Result<OnFTReceivedResponse, ContractError> {
-        let ress: OnFTReceivedResponse = deps.querier.query_wasm_smart(
+        on_receive_error: Option<OnReceiveError>,
+    ) -> Result<Response, ContractError> {
+        let owner_addr = deps.api.addr_validate(&owner)?;
+
+        _deduct_allowance(deps.storage, &env.block, &owner_addr, &info.sender, amount)?;
+
+        _move_and_notify(
+            deps,
+            &env,
+            &info.sender,
+            &owner_addr,
             recipient,
-            &QueryMsg::OnFTReceived {
-                sender: env.contract.address.clone().into(),
-                owner: info.sender.to_string(),
-                amount,
-            },
-        )?;
-        Ok(ress)
+            amount,
+            None,
+            on_receive_error.unwrap_or_default(),
+        )
     }
 
-    pub fn transfer_from(
+    #[allow(clippy::too_many_arguments)]
+    pub fn send(
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        owner: String,
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+        on_receive_error: Option<OnReceiveError>,
+    ) -> Result<Response, ContractError> {
+        if amount == Uint128::zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if !is_contract(deps.as_ref(), &contract) {
+            return Err(ContractError::NotContract {});
+        }
+
+        _move_and_notify(
+            deps,
+            &env,
+            &info.sender,
+            &info.sender,
+            contract,
+            amount,
+            Some(msg),
+            on_receive_error.unwrap_or_default(),
+        )
+    }
+
+    // moves the tokens and, if the recipient is a smart contract, dispatches its Receive hook.
+    // a failing hook is handled in `reply` according to on_error.
+    #[allow(clippy::too_many_arguments)]
+    fn _move_and_notify(
+        deps: DepsMut,
+        env: &Env,
+        operator: &Addr,
+        owner: &Addr,
         recipient: String,
         amount: Uint128,
+        msg: Option<Binary>,
+        on_error: OnReceiveError,
     ) -> Result<Response, ContractError> {
-        let owner_addr = deps.api.addr_validate(&owner)?;
-        let rcpt_addr = deps.api.addr_validate(&recipient)?;
+        assert_not_paused(deps.storage)?;
 
-        _deduct_allowance(deps.storage, &owner_addr, &info.sender, amount)?;
+        let rcpt_addr = deps.api.addr_validate(&recipient)?;
+        assert_not_frozen(deps.storage, &[owner, operator, &rcpt_addr])?;
 
-        BALANCES.update(
-            deps.storage,
-            &owner_addr,
-            |balance: Option<Uint128>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default().checked_sub(amount)?)
-            },
-        )?;
-        BALANCES.update(
-            deps.storage,
-            &rcpt_addr,
-            |balance: Option<Uint128>| -> StdResult<_> {
-                Ok(balance.unwrap_or_default().checked_add(amount)?)
-            },
+        // check the recipient before moving anything
+        let check = _check_receiver(
+            deps.as_ref(),
+            env,
+            operator,
+            owner,
+            &recipient,
+            amount,
+            msg.as_ref(),
         )?;
 
-        let mut res = Response::default().add_event(transfer_event(
+        _transfer_balance(deps.storage, &env.block, owner, &rcpt_addr, amount)?;
+
+        let res = Response::default().add_event(transfer_event(
             owner.as_ref(),
             recipient.as_ref(),
             amount,
         ));
+        _notify_receiver(
+            deps.storage,
+            env,
+            res,
+            check,
+            operator,
+            owner,
+            rcpt_addr,
+            amount,
+            msg,
+            on_error,
+        )
+    }
 
-        // if recipient is smart contract
-        if is_contract(deps.as_ref(), &recipient) {
-            let rr = can_receive(deps, &recipient, &env, info, amount)?;
-            if !rr.enable {
-                return Err(ContractError::NonTransferable {});
+    /// Moves tokens on behalf of from, who signed a RelayedTransferSignDoc. The relayer
+    /// submitting it is paid fee out of from's balance.
+    #[allow(clippy::too_many_arguments)]
+    pub fn relayed_transfer(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        from: String,
+        recipient: String,
+        amount: Uint128,
+        fee: Uint128,
+        nonce: u64,
+        deadline: Expiration,
+        relayer: Option<String>,
+        pubkey: Binary,
+        signature: Binary,
+    ) -> Result<Response, ContractError> {
+        if amount == Uint128::zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        if deadline.is_expired(&env.block) {
+            return Err(ContractError::SignatureExpired {});
+        }
+        // keeps others from copying the message out of the mempool to take the fee
+        if let Some(relayer) = &relayer {
+            if relayer != info.sender.as_str() {
+                return Err(ContractError::WrongRelayer {
+                    relayer: relayer.clone(),
+                });
             }
+        }
 
-            let sub_msg = WasmMsg::Execute {
-                contract_addr: recipient,
-                msg: to_binary(&ExecuteMsg::Receive {
-                    amount,
-                    sender: env.contract.address.into(),
-                })?,
-                funds: vec![],
-            };
-            res = res.add_submessage(SubMsg::new(sub_msg));
+        let from_addr = deps.api.addr_validate(&from)?;
+        let sign_doc = RelayedTransferSignDoc {
+            chain_id: env.block.chain_id.clone(),
+            contract: env.contract.address.to_string(),
+            from,
+            recipient: recipient.clone(),
+            amount,
+            fee,
+            nonce,
+            deadline,
+            relayer,
         };
+        signature::verify_signature(deps.as_ref(), &from_addr, &sign_doc, &pubkey, &signature)?;
+        signature::use_nonce(deps.storage, &from_addr, nonce)?;
 
-        Ok(res)
+        // the fee is a plain movement, the relayer gets no receive hook
+        let mut fee_events = vec![];
+        if !fee.is_zero() {
+            assert_not_frozen(deps.storage, &[&from_addr, &info.sender])?;
+            _transfer_balance(deps.storage, &env.block, &from_addr, &info.sender, fee)?;
+            fee_events.push(transfer_event(
+                from_addr.as_ref(),
+                info.sender.as_ref(),
+                fee,
+            ));
+        }
+
+        let res = _move_and_notify(
+            deps,
+            &env,
+            &from_addr,
+            &from_addr,
+            recipient,
+            amount,
+            None,
+            OnReceiveError::default(),
+        )?;
+        Ok(res.add_events(fee_events))
     }
 
-    pub fn _deduct_allowance(
-        storage: &mut dyn Storage,
-        owner: &Addr,
-        spender: &Addr,
-        amount: Uint128,
-    ) -> Result<AllowanceResponse, ContractError> {
-        let update_fn = |current: Option<AllowanceResponse>| -> _ {
-            match current {
-                Some(mut a) => {
-                    a.allowance = a
-                        .allowance
-                        .checked_sub(amount)
-                        .map_err(StdError::overflow)?;
-                    Ok(a)
-                }
-                None => Err(ContractError::NoAllowance {}),
-            }
-        };
-        ALLOWANCES.update(storage, (owner, spender), update_fn)?;
-        ALLOWANCES_SPENDER.update(storage, (spender, owner), update_fn)
+    pub fn batch_transfer(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        transfers: Vec<TransferItem>,
+        on_receive_error: Option<OnReceiveError>,
+    ) -> Result<Response, ContractError> {
+        _batch_move(
+            deps,
+            &env,
+            &info.sender,
+            &info.sender,
+            transfers,
+            on_receive_error.unwrap_or_default(),
+            false,
+        )
     }
 
-    pub fn approve(
+    pub fn batch_transfer_from(
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
-        spender: String,
-        amount: Uint128,
-        current_allowance: Uint128,
+        owner: String,
+        transfers: Vec<TransferItem>,
+        on_receive_error: Option<OnReceiveError>,
     ) -> Result<Response, ContractError> {
-        let spender_addr = deps.api.addr_validate(&spender)?;
-        if spender_addr == info.sender {
-            return Err(ContractError::CannotSetOwnAccount {});
+        let owner_addr = deps.api.addr_validate(&owner)?;
+
+        _batch_move(
+            deps,
+            &env,
+            &info.sender,
+            &owner_addr,
+            transfers,
+            on_receive_error.unwrap_or_default(),
+            true,
+        )
+    }
+
+    // checks every leg, then debits the owner once and credits each recipient.
+    // spends the operator's allowance when spend_allowance is set, like TransferFrom even if
+    // the operator is the owner.
+    fn _batch_move(
+        deps: DepsMut,
+        env: &Env,
+        operator: &Addr,
+        owner: &Addr,
+        transfers: Vec<TransferItem>,
+        on_error: OnReceiveError,
+        spend_allowance: bool,
+    ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+
+        if transfers.is_empty() {
+            return Err(ContractError::EmptyBatch {});
         }
+        assert_not_frozen(deps.storage, &[owner, operator])?;
 
-        let key = (&info.sender, &spender_addr);
-        fn reverse<'a>(t: (&'a Addr, &'a Addr)) -> (&'a Addr, &'a Addr) {
-            (t.1, t.0)
+        let mut total = Uint128::zero();
+        let mut legs = Vec::with_capacity(transfers.len());
+        for (index, transfer) in transfers.into_iter().enumerate() {
+            let mut check_leg = || -> Result<_, ContractError> {
+                if transfer.amount == Uint128::zero() {
+                    return Err(ContractError::InvalidZeroAmount {});
+                }
+                let rcpt_addr = deps.api.addr_validate(&transfer.recipient)?;
+                assert_not_frozen(deps.storage, &[&rcpt_addr])?;
+                assert_allowlisted(deps.storage, &rcpt_addr)?;
+                let check = _check_receiver(
+                    deps.as_ref(),
+                    env,
+                    operator,
+                    owner,
+                    &transfer.recipient,
+                    transfer.amount,
+                    None,
+                )?;
+                total = total
+                    .checked_add(transfer.amount)
+                    .map_err(StdError::overflow)?;
+                Ok((rcpt_addr, check))
+            };
+            let (rcpt_addr, check) =
+                check_leg().map_err(|error| ContractError::InvalidBatchLeg {
+                    index,
+                    error: Box::new(error),
+                })?;
+            legs.push((rcpt_addr, transfer.amount, check));
         }
 
-        let old_allowance = ALLOWANCES.may_load(deps.storage, key)?.unwrap_or_default();
-        if current_allowance != old_allowance.allowance {
-            return Err(ContractError::InvalidCurrentAllowance {});
+        if spend_allowance {
+            _deduct_allowance(deps.storage, &env.block, owner, operator, total)?;
         }
-        if amount == Uint128::zero() {
-            ALLOWANCES.remove(deps.storage, key);
-            ALLOWANCES_SPENDER.remove(deps.storage, reverse(key));
-        } else {
-            let new_allowance = AllowanceResponse { allowance: amount };
-            ALLOWANCES.save(deps.storage, key, &new_allowance)?;
-            ALLOWANCES_SPENDER.save(deps.storage, reverse(key), &new_allowance)?;
+        _sub_balance(deps.storage, &env.block, owner, total)?;
+
+        let mut res = Response::default();
+        for (rcpt_addr, amount, check) in legs {
+            _add_balance(deps.storage, &env.block, &rcpt_addr, amount)?;
+            res = res.add_event(transfer_event(owner.as_ref(), rcpt_addr.as_ref(), amount));
+            res = _notify_receiver(
+                deps.storage,
+                env,
+                res,
+                check,
+                operator,
+                owner,
+                rcpt_addr,
+                amount,
+                None,
+                on_error.clone(),
+            )?;
         }
 
-        Ok(Response::new().add_event(approval_event(
-            info.sender.as_ref(),
-            spender.as_ref(),
-            old_allowance.allowance,
-            amount,
-        )))
+        Ok(res)
     }
 
-    pub fn receive(
-        deps: DepsMut,
-        _env: Env,
-        info: MessageInfo,
-        sender: String,
-        _amount: Uint128,
-    ) -> Result<Response, ContractError> {
-        // check sender is real sender and contract.
-        if info.sender != sender || !is_contract(deps.as_ref(), &sender) {
-            return Err(ContractError::Unauthorized {});
-        }
+    /// How a recipient takes part in a transfer
+    struct ReceiverCheck {
+        policy: ReceiverPolicy,
+        // true if the Receive hook must be dispatched
+        notify: bool,
+    }
 
-        // add triggered features here
-        // if you don't want to receive token with any reason, please return error.
+    // returns None for accounts. for smart contracts, decides on the Receive hook
+    // following the receiver policy and fails if the contract refuses the tokens.
+    fn _check_receiver(
+        deps: Deps,
+        env: &Env,
+        operator: &Addr,
+        owner: &Addr,
+        recipient: &str,
+        amount: Uint128,
+        msg: Option<&Binary>,
+    ) -> Result<Option<ReceiverCheck>, ContractError> {
+        if !is_contract(deps, recipient) {
+            return Ok(None);
+        }
 
-        Ok(Response::default())
+        // Send always asks for the hook, plain transfers follow the token's receiver policy
+        let policy = match msg {
+            Some(_) => ReceiverPolicy::Strict,
+            None => TOKEN_INFO.load(deps.storage)?.receiver_policy,
+        };
+        let notify = _ask_receiver(deps, env, &policy, operator, owner, recipient, amount, msg)?;
+        Ok(Some(ReceiverCheck { policy, notify }))
     }
-}
-
-fn is_contract(deps: Deps<'_>, recipient: &str) -> bool {
-    deps.querier
-        .query_wasm_contract_info(recipient.to_owned())
-        .is_ok()
-}
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Info {} => to_binary(&query_info(deps)?),
-        QueryMsg::TotalSupply {} => to_binary(&query_info(deps)?),
-        QueryMsg::Balance { owner } => to_binary(&query_balance(deps, owner)?),
-        QueryMsg::Allowance { owner, spender } => {
-            to_binary(&query_allowance(deps, owner, spender)?)
+    /// Applies the token's receiver policy to a cw20 Send to contract. The cw20 Receive hook
+    /// is always dispatched by the caller, this only makes sure the contract accepts.
+    pub fn check_cw20_receiver(
+        deps: Deps,
+        env: &Env,
+        operator: &Addr,
+        owner: &Addr,
+        contract: &str,
+        amount: Uint128,
+        msg: &Binary,
+    ) -> Result<(), ContractError> {
+        if !is_contract(deps, contract) {
+            return Ok(());
         }
-        QueryMsg::OnFTReceived {
-            sender,
+        let policy = TOKEN_INFO.load(deps.storage)?.receiver_policy;
+        _ask_receiver(
+            deps,
+            env,
+            &policy,
+            operator,
             owner,
+            contract,
             amount,
-        } => to_binary(&query_on_ft_received(deps, sender, owner, amount)?),
+            Some(msg),
+        )?;
+        Ok(())
     }
-}
 
-pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
-    let info = TOKEN_INFO.load(deps.storage)?;
-    Ok(InfoResponse {
-        name: info.name,
-        symbol: info.symbol,
-        decimal: info.decimals,
-        total_supply: info.total_supply,
-    })
-}
+    // asks the contract through OnFTReceived unless policy is Off. fails if it refuses the
+    // tokens, returns whether it answered.
+    #[allow(clippy::too_many_arguments)]
+    fn _ask_receiver(
+        deps: Deps,
+        env: &Env,
+        policy: &ReceiverPolicy,
+        operator: &Addr,
+        owner: &Addr,
+        recipient: &str,
+        amount: Uint128,
+        msg: Option<&Binary>,
+    ) -> Result<bool, ContractError> {
+        if *policy == ReceiverPolicy::Off {
+            return Ok(false);
+        }
 
-pub fn query_total_supply(deps: Deps) -> StdResult<TotalSupplyResponse> {
+        // check if define onReceived
+        let rr = can_receive(deps, recipient, env, operator, owner, amount, msg.cloned());
+        match rr {
+            Ok(rr) if !rr.enable => Err(ContractError::NonTransferable {}),
+            Ok(_) => Ok(true),
+            // contracts without OnFTReceived are accepted as is
+            Err(_) if *policy == ReceiverPolicy::Lenient => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    // reports the receiver check and adds the Receive hook to the response if it is due
+    #[allow(clippy::too_many_arguments)]
+    fn _notify_receiver(
+        storage: &mut dyn Storage,
+        env: &Env,
+        res: Response,
+        check: Option<ReceiverCheck>,
+        operator: &Addr,
+        owner: &Addr,
+        recipient: Addr,
+        amount: Uint128,
+        msg: Option<Binary>,
+        on_error: OnReceiveError,
+    ) -> Result<Response, ContractError> {
+        let check = match check {
+            Some(check) => check,
+            None => return Ok(res),
+        };
+        let res = res
+            .add_attribute("receiver_policy", check.policy.as_str())
+            .add_attribute("on_ft_received", check.notify.to_string());
+        if !check.notify {
+            return Ok(res);
+        }
+
+        let reply_id = NEXT_REPLY_ID.may_load(storage)?.unwrap_or_default();
+        NEXT_REPLY_ID.save(storage, &reply_id.wrapping_add(1))?;
+        PENDING_RECEIVES.save(
+            storage,
+            reply_id,
+            &PendingReceive {
+                owner: owner.clone(),
+                recipient: recipient.clone(),
+                amount,
+                on_error,
+            },
+        )?;
+
+        let sub_msg = WasmMsg::Execute {
+            contract_addr: recipient.into(),
+            msg: to_binary(&ExecuteMsg::Receive {
+                token: env.contract.address.to_string(),
+                from: owner.to_string(),
+                operator: operator.to_string(),
+                amount,
+                msg,
+            })?,
+            funds: vec![],
+        };
+        // replies on success too, so the pending entry never outlives the hook
+        Ok(res.add_submessage(SubMsg::reply_always(sub_msg, reply_id)))
+    }
+
+    fn can_receive(
+        deps: Deps,
+        recipient: &str,
+        env: &Env,
+        operator: &Addr,
+        owner: &Addr,
+        amount: Uint128,
+        msg: Option<Binary>,
+    ) -> Result<OnFTReceivedResponse, ContractError> {
+        let ress: OnFTReceivedResponse = deps.querier.query_wasm_smart(
+            recipient,
+            &QueryMsg::OnFTReceived {
+                token: env.contract.address.to_string(),
+                from: owner.to_string(),
+                operator: operator.to_string(),
+                amount,
+                msg,
+            },
+        )?;
+        Ok(ress)
+    }
+
+    pub fn _transfer_balance(
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        from: &Addr,
+        to: &Addr,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        _sub_balance(storage, block, from, amount)?;
+        _add_balance(storage, block, to, amount)
+    }
+
+    // every debit goes through here, so locked tokens cannot leave by any path
+    pub fn _sub_balance(
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        owner: &Addr,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        let locked = locked_balance(storage, block, owner)?;
+        if !locked.is_zero() {
+            let balance = BALANCES.may_load(storage, owner)?.unwrap_or_default();
+            let spendable = balance.saturating_sub(locked);
+            if amount > spendable {
+                return Err(ContractError::TokensLocked { spendable });
+            }
+        }
+        BALANCES.update(storage, owner, block.height, |balance| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_sub(amount)?)
+        })?;
+        if let Some(delegate) = DELEGATES.may_load(storage, owner)? {
+            _sub_voting_power(storage, block, &delegate, amount)?;
+        }
+        Ok(())
+    }
+
+    // every credit goes through here, so the allowlist applies to all recipients
+    pub fn _add_balance(
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        owner: &Addr,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        assert_allowlisted(storage, owner)?;
+        BALANCES.update(storage, owner, block.height, |balance| -> StdResult<_> {
+            Ok(balance.unwrap_or_default().checked_add(amount)?)
+        })?;
+        if let Some(delegate) = DELEGATES.may_load(storage, owner)? {
+            _add_voting_power(storage, block, &delegate, amount)?;
+        }
+        Ok(())
+    }
+
+    fn _sub_voting_power(
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        delegate: &Addr,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        VOTING_POWER.update(storage, delegate, block.height, |power| -> StdResult<_> {
+            Ok(power.unwrap_or_default().checked_sub(amount)?)
+        })?;
+        Ok(())
+    }
+
+    fn _add_voting_power(
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        delegate: &Addr,
+        amount: Uint128,
+    ) -> StdResult<()> {
+        VOTING_POWER.update(storage, delegate, block.height, |power| -> StdResult<_> {
+            Ok(power.unwrap_or_default().checked_add(amount)?)
+        })?;
+        Ok(())
+    }
+
+    /// Gives the sender's voting power to delegatee, which may be the sender itself.
+    /// Accounts that never delegated have no voting power.
+    pub fn delegate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        delegatee: String,
+    ) -> Result<Response, ContractError> {
+        let delegatee = deps.api.addr_validate(&delegatee)?;
+        let previous = DELEGATES.may_load(deps.storage, &info.sender)?;
+        let balance = BALANCES
+            .may_load(deps.storage, &info.sender)?
+            .unwrap_or_default();
+
+        if let Some(previous) = &previous {
+            _sub_voting_power(deps.storage, &env.block, previous, balance)?;
+        }
+        _add_voting_power(deps.storage, &env.block, &delegatee, balance)?;
+        DELEGATES.save(deps.storage, &info.sender, &delegatee)?;
+
+        Ok(Response::new().add_event(delegate_changed_event(
+            info.sender.as_ref(),
+            previous.as_ref().map(Addr::as_str).unwrap_or_default(),
+            delegatee.as_ref(),
+        )))
+    }
+
+    pub fn _deduct_allowance(
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        owner: &Addr,
+        spender: &Addr,
+        amount: Uint128,
+    ) -> Result<AllowanceResponse, ContractError> {
+        let update_fn = |current: Option<AllowanceResponse>| -> _ {
+            match current {
+                Some(a) if a.expires.is_expired(block) => Err(ContractError::Expired {}),
+                Some(mut a) => {
+                    a.allowance = a
+                        .allowance
+                        .checked_sub(amount)
+                        .map_err(StdError::overflow)?;
+                    Ok(a)
+                }
+                None => Err(ContractError::NoAllowance {}),
+            }
+        };
+        ALLOWANCES.update(storage, (owner, spender), update_fn)?;
+        ALLOWANCES_SPENDER.update(storage, (spender, owner), update_fn)
+    }
+
+    pub fn approve(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        amount: Uint128,
+        current_allowance: Uint128,
+        expires: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        if spender_addr == info.sender {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+        assert_not_frozen(deps.storage, &[&info.sender, &spender_addr])?;
+
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+
+        let old_allowance = ALLOWANCES
+            .may_load(deps.storage, (&info.sender, &spender_addr))?
+            .unwrap_or_default();
+        if current_allowance != old_allowance.allowance {
+            return Err(ContractError::InvalidCurrentAllowance {});
+        }
+        _set_allowance(deps.storage, &info.sender, &spender_addr, amount, expires)?;
+
+        Ok(Response::new().add_event(approval_event(
+            info.sender.as_ref(),
+            spender.as_ref(),
+            old_allowance.allowance,
+            amount,
+        )))
+    }
+
+    pub fn increase_allowance(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        if spender_addr == info.sender {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+        assert_not_frozen(deps.storage, &[&info.sender, &spender_addr])?;
+        if let Some(exp) = expires {
+            if exp.is_expired(&env.block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+        }
+
+        let old_allowance = ALLOWANCES
+            .may_load(deps.storage, (&info.sender, &spender_addr))?
+            .unwrap_or_default();
+        let new_amount = old_allowance
+            .allowance
+            .checked_add(amount)
+            .map_err(StdError::overflow)?;
+        _set_allowance(
+            deps.storage,
+            &info.sender,
+            &spender_addr,
+            new_amount,
+            expires.unwrap_or(old_allowance.expires),
+        )?;
+
+        Ok(Response::new().add_event(approval_event(
+            info.sender.as_ref(),
+            spender.as_ref(),
+            old_allowance.allowance,
+            new_amount,
+        )))
+    }
+
+    pub fn decrease_allowance(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        if spender_addr == info.sender {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+        assert_not_frozen(deps.storage, &[&info.sender, &spender_addr])?;
+        if let Some(exp) = expires {
+            if exp.is_expired(&env.block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+        }
+
+        let old_allowance = ALLOWANCES
+            .may_load(deps.storage, (&info.sender, &spender_addr))?
+            .ok_or(ContractError::NoAllowance {})?;
+        // decreasing below zero removes the allowance
+        let new_amount = old_allowance.allowance.saturating_sub(amount);
+        _set_allowance(
+            deps.storage,
+            &info.sender,
+            &spender_addr,
+            new_amount,
+            expires.unwrap_or(old_allowance.expires),
+        )?;
+
+        Ok(Response::new().add_event(approval_event(
+            info.sender.as_ref(),
+            spender.as_ref(),
+            old_allowance.allowance,
+            new_amount,
+        )))
+    }
+
+    /// Sets an allowance authorized by the owner's signature instead of its transaction
+    #[allow(clippy::too_many_arguments)]
+    pub fn permit(
+        deps: DepsMut,
+        env: Env,
+        _info: MessageInfo,
+        owner: String,
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+        nonce: u64,
+        pubkey: Binary,
+        signature: Binary,
+    ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let spender_addr = deps.api.addr_validate(&spender)?;
+        if spender_addr == owner_addr {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+        assert_not_frozen(deps.storage, &[&owner_addr, &spender_addr])?;
+
+        let sign_doc = PermitSignDoc {
+            chain_id: env.block.chain_id.clone(),
+            contract: env.contract.address.to_string(),
+            owner,
+            spender,
+            amount,
+            expires,
+            nonce,
+        };
+        signature::verify_signature(deps.as_ref(), &owner_addr, &sign_doc, &pubkey, &signature)?;
+        signature::use_nonce(deps.storage, &owner_addr, nonce)?;
+
+        let expires = expires.unwrap_or_default();
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+
+        let old_allowance = ALLOWANCES
+            .may_load(deps.storage, (&owner_addr, &spender_addr))?
+            .unwrap_or_default();
+        _set_allowance(deps.storage, &owner_addr, &spender_addr, amount, expires)?;
+
+        Ok(Response::new().add_event(approval_event(
+            owner_addr.as_ref(),
+            spender_addr.as_ref(),
+            old_allowance.allowance,
+            amount,
+        )))
+    }
+
+    // writes the allowance under both indexes, removing it when it reaches zero
+    fn _set_allowance(
+        storage: &mut dyn Storage,
+        owner: &Addr,
+        spender: &Addr,
+        amount: Uint128,
+        expires: Expiration,
+    ) -> StdResult<()> {
+        if amount == Uint128::zero() {
+            ALLOWANCES.remove(storage, (owner, spender));
+            ALLOWANCES_SPENDER.remove(storage, (spender, owner));
+        } else {
+            let allowance = AllowanceResponse {
+                allowance: amount,
+                expires,
+            };
+            ALLOWANCES.save(storage, (owner, spender), &allowance)?;
+            ALLOWANCES_SPENDER.save(storage, (spender, owner), &allowance)?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn receive(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token: String,
+        _from: String,
+        _operator: String,
+        _amount: Uint128,
+        _msg: Option<Binary>,
+    ) -> Result<Response, ContractError> {
+        // check the hook is called by the token contract itself.
+        if info.sender != token || !is_contract(deps.as_ref(), &token) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // add triggered features here
+        // if you don't want to receive token with any reason, please return error.
+
+        Ok(Response::default())
+    }
+
+    pub fn mint(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: String,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+
+        if amount == Uint128::zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let config = TOKEN_INFO.load(deps.storage)?;
+        if config
+            .mint
+            .as_ref()
+            .map(|v| v.minter != info.sender)
+            .unwrap_or(true)
+        {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // update supply and enforce cap
+        let total_supply = TOTAL_SUPPLY
+            .load(deps.storage)?
+            .checked_add(amount)
+            .map_err(StdError::overflow)?;
+        if let Some(limit) = config.get_cap() {
+            if total_supply > limit {
+                return Err(ContractError::CannotExceedCap {});
+            }
+        }
+        TOTAL_SUPPLY.save(deps.storage, &total_supply, env.block.height)?;
+
+        let rcpt_addr = deps.api.addr_validate(&recipient)?;
+        assert_not_frozen(deps.storage, &[&rcpt_addr])?;
+        _add_balance(deps.storage, &env.block, &rcpt_addr, amount)?;
+
+        Ok(Response::new().add_event(mint_event(info.sender.as_ref(), recipient.as_ref(), amount)))
+    }
+
+    pub fn update_minter(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        new_minter: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let mut config = TOKEN_INFO.load(deps.storage)?;
+        let mint = config.mint.as_ref().ok_or(ContractError::Unauthorized {})?;
+        if mint.minter != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // dropping the minter disables minting for good
+        config.mint = match new_minter {
+            Some(new_minter) => Some(MinterData {
+                minter: deps.api.addr_validate(&new_minter)?,
+                cap: mint.cap,
+            }),
+            None => None,
+        };
+        TOKEN_INFO.save(deps.storage, &config)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_minter")
+            .add_attribute(
+                "new_minter",
+                config
+                    .mint
+                    .map(|m| m.minter.into_string())
+                    .unwrap_or_else(|| "None".to_string()),
+            ))
+    }
+
+    pub fn update_marketing(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        project: Option<String>,
+        description: Option<String>,
+        marketing: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let mut marketing_info = MARKETING_INFO
+            .may_load(deps.storage)?
+            .ok_or(ContractError::Unauthorized {})?;
+        if marketing_info.marketing.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // an empty string clears the field
+        let clear = |value: String| {
+            if value.trim().is_empty() {
+                None
+            } else {
+                Some(value)
+            }
+        };
+        if let Some(project) = project {
+            marketing_info.project = clear(project);
+        }
+        if let Some(description) = description {
+            marketing_info.description = clear(description);
+        }
+        if let Some(marketing) = marketing {
+            marketing_info.marketing = clear(marketing)
+                .map(|addr| deps.api.addr_validate(&addr))
+                .transpose()?;
+        }
+
+        if marketing_info.project.is_none()
+            && marketing_info.description.is_none()
+            && marketing_info.marketing.is_none()
+            && marketing_info.logo.is_none()
+        {
+            MARKETING_INFO.remove(deps.storage);
+        } else {
+            MARKETING_INFO.save(deps.storage, &marketing_info)?;
+        }
+
+        Ok(Response::new().add_attribute("action", "update_marketing"))
+    }
+
+    pub fn upload_logo(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        logo: Logo,
+    ) -> Result<Response, ContractError> {
+        let mut marketing_info = MARKETING_INFO
+            .may_load(deps.storage)?
+            .ok_or(ContractError::Unauthorized {})?;
+        if marketing_info.marketing.as_ref() != Some(&info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        verify_logo(&logo)?;
+        LOGO.save(deps.storage, &logo)?;
+        marketing_info.logo = Some(logo_info(logo));
+        MARKETING_INFO.save(deps.storage, &marketing_info)?;
+
+        Ok(Response::new().add_attribute("action", "upload_logo"))
+    }
+
+    // what the MarketingInfo query reports about a logo. embedded data is served by DownloadLogo.
+    pub fn logo_info(logo: Logo) -> LogoInfo {
+        match logo {
+            Logo::Url(url) => LogoInfo::Url(url),
+            Logo::Embedded(_) => LogoInfo::Embedded,
+        }
+    }
+
+    pub fn verify_logo(logo: &Logo) -> Result<(), ContractError> {
+        match logo {
+            Logo::Url(_) => Ok(()),
+            Logo::Embedded(EmbeddedLogo::Svg(logo)) => verify_xml_logo(logo),
+            Logo::Embedded(EmbeddedLogo::Png(logo)) => verify_png_logo(logo),
+        }
+    }
+
+    // checks that the data starts with an xml preamble or an svg tag, this is not a full
+    // svg validation
+    fn verify_xml_logo(logo: &[u8]) -> Result<(), ContractError> {
+        if logo.len() > LOGO_SIZE_CAP {
+            return Err(ContractError::LogoTooBig {});
+        }
+
+        let preamble = logo
+            .split_inclusive(|c| *c == b'>')
+            .next()
+            .ok_or(ContractError::InvalidXmlPreamble {})?;
+        const PREFIX: &[u8] = b"<?xml ";
+        const POSTFIX: &[u8] = b"?>";
+        let has_preamble = preamble.starts_with(PREFIX) && preamble.ends_with(POSTFIX);
+        if !has_preamble && !logo.starts_with(b"<svg") {
+            return Err(ContractError::InvalidXmlPreamble {});
+        }
+        Ok(())
+    }
+
+    // checks the png signature, this is not a full png validation
+    fn verify_png_logo(logo: &[u8]) -> Result<(), ContractError> {
+        // 0x89 P N G \r \n 0x1A \n
+        const HEADER: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+        if logo.len() > LOGO_SIZE_CAP {
+            return Err(ContractError::LogoTooBig {});
+        }
+        if !logo.starts_with(&HEADER) {
+            return Err(ContractError::InvalidPngHeader {});
+        }
+        Ok(())
+    }
+
+    pub fn pause(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        _set_paused(deps, info, true)
+    }
+
+    pub fn unpause(deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        _set_paused(deps, info, false)
+    }
+
+    fn _set_paused(
+        deps: DepsMut,
+        info: MessageInfo,
+        paused: bool,
+    ) -> Result<Response, ContractError> {
+        if PAUSER.may_load(deps.storage)? != Some(info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+        PAUSED.save(deps.storage, &paused)?;
+
+        Ok(Response::new()
+            .add_attribute("action", if paused { "pause" } else { "unpause" })
+            .add_attribute("paused", paused.to_string()))
+    }
+
+    /// The owner may hand the pauser role to another account. Setting None leaves nobody able
+    /// to pause or unpause the token.
+    pub fn update_pauser(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        new_pauser: Option<String>,
+    ) -> Result<Response, ContractError> {
+        ownership::assert_owner(deps.storage, &info.sender)?;
+
+        let new_pauser = new_pauser
+            .map(|pauser| deps.api.addr_validate(&pauser))
+            .transpose()?;
+        match &new_pauser {
+            Some(pauser) => PAUSER.save(deps.storage, pauser)?,
+            None => PAUSER.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "update_pauser")
+            .add_attribute(
+                "new_pauser",
+                new_pauser
+                    .map(Addr::into_string)
+                    .unwrap_or_else(|| "None".to_string()),
+            ))
+    }
+
+    pub fn assert_not_paused(storage: &dyn Storage) -> Result<(), ContractError> {
+        if PAUSED.may_load(storage)?.unwrap_or_default() {
+            return Err(ContractError::Paused {});
+        }
+        Ok(())
+    }
+
+    pub fn freeze(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        address: String,
+    ) -> Result<Response, ContractError> {
+        _set_frozen(deps, info, address, true)
+    }
+
+    pub fn unfreeze(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        address: String,
+    ) -> Result<Response, ContractError> {
+        _set_frozen(deps, info, address, false)
+    }
+
+    fn _set_frozen(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+        frozen: bool,
+    ) -> Result<Response, ContractError> {
+        if COMPLIANCE.may_load(deps.storage)? != Some(info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let addr = deps.api.addr_validate(&address)?;
+        if frozen {
+            FROZEN.save(deps.storage, &addr, &Empty {})?;
+        } else {
+            FROZEN.remove(deps.storage, &addr);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", if frozen { "freeze" } else { "unfreeze" })
+            .add_attribute("address", addr))
+    }
+
+    /// The owner may hand the compliance role to another account. Setting None leaves nobody
+    /// able to freeze or unfreeze accounts.
+    pub fn update_compliance(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        new_compliance: Option<String>,
+    ) -> Result<Response, ContractError> {
+        ownership::assert_owner(deps.storage, &info.sender)?;
+
+        let new_compliance = new_compliance
+            .map(|compliance| deps.api.addr_validate(&compliance))
+            .transpose()?;
+        match &new_compliance {
+            Some(compliance) => COMPLIANCE.save(deps.storage, compliance)?,
+            None => COMPLIANCE.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "update_compliance")
+            .add_attribute(
+                "new_compliance",
+                new_compliance
+                    .map(Addr::into_string)
+                    .unwrap_or_else(|| "None".to_string()),
+            ))
+    }
+
+    /// Fails with AccountFrozen for the first frozen address
+    pub fn assert_not_frozen(
+        storage: &dyn Storage,
+        addresses: &[&Addr],
+    ) -> Result<(), ContractError> {
+        for addr in addresses {
+            if FROZEN.has(storage, addr) {
+                return Err(ContractError::AccountFrozen {
+                    address: addr.to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    pub fn add_to_allowlist(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        address: String,
+    ) -> Result<Response, ContractError> {
+        _set_allowlisted(deps, info, address, true)
+    }
+
+    pub fn remove_from_allowlist(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        address: String,
+    ) -> Result<Response, ContractError> {
+        _set_allowlisted(deps, info, address, false)
+    }
+
+    // the allowlist is kept by the compliance role, like the frozen accounts
+    fn _set_allowlisted(
+        deps: DepsMut,
+        info: MessageInfo,
+        address: String,
+        allowlisted: bool,
+    ) -> Result<Response, ContractError> {
+        if COMPLIANCE.may_load(deps.storage)? != Some(info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let addr = deps.api.addr_validate(&address)?;
+        if allowlisted {
+            ALLOWLIST.save(deps.storage, &addr, &Empty {})?;
+        } else {
+            ALLOWLIST.remove(deps.storage, &addr);
+        }
+
+        Ok(Response::new()
+            .add_attribute(
+                "action",
+                if allowlisted {
+                    "add_to_allowlist"
+                } else {
+                    "remove_from_allowlist"
+                },
+            )
+            .add_attribute("address", addr))
+    }
+
+    /// Only the owner can switch allowlist mode. Balances already held outside the allowlist
+    /// are kept, but cannot be added to.
+    pub fn update_allowlist_mode(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        allowlist_only: bool,
+    ) -> Result<Response, ContractError> {
+        ownership::assert_owner(deps.storage, &info.sender)?;
+
+        TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
+            info.allowlist_only = allowlist_only;
+            Ok(info)
+        })?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_allowlist_mode")
+            .add_attribute("allowlist_only", allowlist_only.to_string()))
+    }
+
+    /// Fails with NotAllowlisted if the token is in allowlist mode and addr is not on the list
+    pub fn assert_allowlisted(storage: &dyn Storage, addr: &Addr) -> Result<(), ContractError> {
+        if TOKEN_INFO.load(storage)?.allowlist_only && !ALLOWLIST.has(storage, addr) {
+            return Err(ContractError::NotAllowlisted {
+                address: addr.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn update_receiver_policy(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        policy: ReceiverPolicy,
+    ) -> Result<Response, ContractError> {
+        ownership::assert_owner(deps.storage, &info.sender)?;
+
+        TOKEN_INFO.update(deps.storage, |mut info| -> StdResult<_> {
+            info.receiver_policy = policy.clone();
+            Ok(info)
+        })?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_receiver_policy")
+            .add_attribute("receiver_policy", policy.as_str()))
+    }
+
+    /// The owner moves amount of its balance to address, where it vests by schedule
+    pub fn create_lockup(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        address: String,
+        amount: Uint128,
+        schedule: VestingSchedule,
+    ) -> Result<Response, ContractError> {
+        ownership::assert_owner(deps.storage, &info.sender)?;
+        assert_not_paused(deps.storage)?;
+        if amount == Uint128::zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        schedule.validate()?;
+
+        let addr = deps.api.addr_validate(&address)?;
+        if addr == info.sender {
+            return Err(ContractError::CannotSetOwnAccount {});
+        }
+        assert_not_frozen(deps.storage, &[&info.sender, &addr])?;
+        // a released lockup can be replaced, the tokens it held stay with the account
+        if !locked_balance(deps.storage, &env.block, &addr)?.is_zero() {
+            return Err(ContractError::LockupExists {});
+        }
+
+        _transfer_balance(deps.storage, &env.block, &info.sender, &addr, amount)?;
+        LOCKUPS.save(deps.storage, &addr, &Lockup { amount, schedule })?;
+
+        Ok(Response::new()
+            .add_event(transfer_event(info.sender.as_ref(), addr.as_ref(), amount))
+            .add_attribute("action", "create_lockup")
+            .add_attribute("address", addr))
+    }
+
+    pub fn deposit(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+
+        let denom = TOKEN_INFO
+            .load(deps.storage)?
+            .native_denom
+            .ok_or(ContractError::NotWrappedNative {})?;
+        let amount = must_pay(&info, &denom)?;
+        assert_not_frozen(deps.storage, &[&info.sender])?;
+
+        TOTAL_SUPPLY.update(deps.storage, env.block.height, |total_supply| {
+            total_supply
+                .unwrap_or_default()
+                .checked_add(amount)
+                .map_err(StdError::overflow)
+        })?;
+        _add_balance(deps.storage, &env.block, &info.sender, amount)?;
+
+        Ok(Response::new().add_event(deposit_event(info.sender.as_ref(), amount)))
+    }
+
+    pub fn withdraw(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+
+        let denom = TOKEN_INFO
+            .load(deps.storage)?
+            .native_denom
+            .ok_or(ContractError::NotWrappedNative {})?;
+        if amount == Uint128::zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        assert_not_frozen(deps.storage, &[&info.sender])?;
+
+        _burn_balance(deps.storage, &env.block, &info.sender, amount)?;
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: coins(amount.u128(), denom),
+            })
+            .add_event(withdraw_event(info.sender.as_ref(), amount)))
+    }
+
+    pub fn burn(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+        if TOKEN_INFO.load(deps.storage)?.native_denom.is_some() {
+            return Err(ContractError::WrappedNativeBurn {});
+        }
+
+        if amount == Uint128::zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        assert_not_frozen(deps.storage, &[&info.sender])?;
+
+        _burn_balance(deps.storage, &env.block, &info.sender, amount)?;
+
+        Ok(Response::new().add_event(burn_event(info.sender.as_ref(), amount)))
+    }
+
+    pub fn burn_from(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        owner: String,
+        amount: Uint128,
+    ) -> Result<Response, ContractError> {
+        assert_not_paused(deps.storage)?;
+        if TOKEN_INFO.load(deps.storage)?.native_denom.is_some() {
+            return Err(ContractError::WrappedNativeBurn {});
+        }
+
+        if amount == Uint128::zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        assert_not_frozen(deps.storage, &[&owner_addr, &info.sender])?;
+
+        _deduct_allowance(deps.storage, &env.block, &owner_addr, &info.sender, amount)?;
+        _burn_balance(deps.storage, &env.block, &owner_addr, amount)?;
+
+        Ok(Response::new().add_event(burn_event(owner.as_ref(), amount)))
+    }
+
+    fn _burn_balance(
+        storage: &mut dyn Storage,
+        block: &BlockInfo,
+        owner: &Addr,
+        amount: Uint128,
+    ) -> Result<(), ContractError> {
+        _sub_balance(storage, block, owner, amount)?;
+        TOTAL_SUPPLY.update(storage, block.height, |total_supply| -> StdResult<_> {
+            Ok(total_supply.unwrap_or_default().checked_sub(amount)?)
+        })?;
+        Ok(())
+    }
+}
+
+fn locked_balance(storage: &dyn Storage, block: &BlockInfo, owner: &Addr) -> StdResult<Uint128> {
+    Ok(LOCKUPS
+        .may_load(storage, owner)?
+        .map(|lockup| lockup.locked(block))
+        .unwrap_or_default())
+}
+
+fn is_contract(deps: Deps<'_>, recipient: &str) -> bool {
+    deps.querier
+        .query_wasm_contract_info(recipient.to_owned())
+        .is_ok()
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let pending = PENDING_RECEIVES.load(deps.storage, msg.id)?;
+    PENDING_RECEIVES.remove(deps.storage, msg.id);
+
+    let reason = match msg.result {
+        SubMsgResult::Ok(_) => return Ok(Response::new()),
+        SubMsgResult::Err(reason) => reason,
+    };
+    match pending.on_error {
+        OnReceiveError::Fail => Err(ContractError::ReceiverRejected {
+            contract: pending.recipient.into(),
+            reason,
+        }),
+        OnReceiveError::Revert => {
+            // the hook's own changes are already rolled back, only the movement is left
+            exec::_transfer_balance(
+                deps.storage,
+                &env.block,
+                &pending.recipient,
+                &pending.owner,
+                pending.amount,
+            )?;
+            Ok(Response::new().add_event(transfer_reverted_event(
+                pending.owner.as_ref(),
+                pending.recipient.as_ref(),
+                pending.amount,
+                &reason,
+            )))
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: stored.contract,
+        });
+    }
+
+    // refuse downgrades
+    let stored_version: Version = stored.version.parse()?;
+    let version: Version = CONTRACT_VERSION.parse()?;
+    if stored_version > version {
+        return Err(ContractError::CannotMigrateVersion {
+            previous_version: stored.version,
+        });
+    }
+
+    // run state migrations for every layout change since the stored version
+    if stored_version < Version::new(0, 2, 0) {
+        let owner = msg
+            .owner
+            .map(|owner| deps.api.addr_validate(&owner))
+            .transpose()?;
+        migrations::v0_2_0(deps.storage, env.block.height, owner)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION))
+}
+
+#[cfg_attr(not(any(feature = "library", feature = "cw20-compat")), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Info {} => to_binary(&query_info(deps)?),
+        QueryMsg::TotalSupply {} => to_binary(&query_info(deps)?),
+        QueryMsg::Balance { owner } => to_binary(&query_balance(deps, owner)?),
+        QueryMsg::BalanceAtHeight { address, height } => {
+            to_binary(&query_balance_at_height(deps, address, height)?)
+        }
+        QueryMsg::TotalSupplyAtHeight { height } => {
+            to_binary(&query_total_supply_at_height(deps, height)?)
+        }
+        QueryMsg::Allowance { owner, spender } => {
+            to_binary(&query_allowance(deps, owner, spender)?)
+        }
+        QueryMsg::OnFTReceived {
+            token,
+            from,
+            operator,
+            amount,
+            msg,
+        } => to_binary(&query_on_ft_received(
+            deps, token, from, operator, amount, msg,
+        )?),
+        QueryMsg::Minter {} => to_binary(&query_minter(deps)?),
+        QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => to_binary(&query_all_allowances(deps, owner, start_after, limit)?),
+        QueryMsg::AllSpenderAllowances {
+            spender,
+            start_after,
+            limit,
+        } => to_binary(&query_all_spender_allowances(
+            deps,
+            spender,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::AllAccounts { start_after, limit } => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::Allowlist { start_after, limit } => {
+            to_binary(&query_allowlist(deps, start_after, limit)?)
+        }
+        QueryMsg::FrozenAccounts { start_after, limit } => {
+            to_binary(&query_frozen_accounts(deps, start_after, limit)?)
+        }
+        QueryMsg::VotingPower { address, height } => {
+            to_binary(&query_voting_power(deps, address, height)?)
+        }
+        QueryMsg::Delegates { address } => to_binary(&query_delegates(deps, address)?),
+        QueryMsg::Nonce { owner } => to_binary(&query_nonce(deps, owner)?),
+        QueryMsg::Reserve {} => to_binary(&query_reserve(deps, env)?),
+        QueryMsg::Vesting { address } => to_binary(&query_vesting(deps, env, address)?),
+        QueryMsg::SpendableBalance { address } => {
+            to_binary(&query_spendable_balance(deps, env, address)?)
+        }
+        QueryMsg::Status {} => to_binary(&query_status(deps)?),
+        QueryMsg::Ownership {} => to_binary(&ownership::get_ownership(deps.storage)?),
+        QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
+        QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+    }
+}
+
+pub fn query_info(deps: Deps) -> StdResult<InfoResponse> {
     let info = TOKEN_INFO.load(deps.storage)?;
+    Ok(InfoResponse {
+        name: info.name,
+        symbol: info.symbol,
+        decimal: info.decimals,
+        total_supply: TOTAL_SUPPLY.load(deps.storage)?,
+        receiver_policy: info.receiver_policy,
+        allowlist_only: info.allowlist_only,
+    })
+}
+
+pub fn query_total_supply(deps: Deps) -> StdResult<TotalSupplyResponse> {
     Ok(TotalSupplyResponse {
-        total_supply: info.total_supply,
+        total_supply: TOTAL_SUPPLY.load(deps.storage)?,
+    })
+}
+
+/// Returns the total supply at the beginning of the block at height
+pub fn query_total_supply_at_height(deps: Deps, height: u64) -> StdResult<TotalSupplyResponse> {
+    let total_supply = TOTAL_SUPPLY
+        .may_load_at_height(deps.storage, height)?
+        .unwrap_or_default();
+    Ok(TotalSupplyResponse { total_supply })
+}
+
+pub fn query_balance(deps: Deps, owner: String) -> StdResult<BalanceResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let balance = BALANCES
+        .may_load(deps.storage, &owner_addr)?
+        .unwrap_or_default();
+    Ok(BalanceResponse { balance })
+}
+
+/// Returns the balance at the beginning of the block at height
+pub fn query_balance_at_height(
+    deps: Deps,
+    address: String,
+    height: u64,
+) -> StdResult<BalanceResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let balance = BALANCES
+        .may_load_at_height(deps.storage, &addr, height)?
+        .unwrap_or_default();
+    Ok(BalanceResponse { balance })
+}
+
+/// Returns the voting power delegated to address, now or at the beginning of the block at
+/// height
+pub fn query_voting_power(
+    deps: Deps,
+    address: String,
+    height: Option<u64>,
+) -> StdResult<VotingPowerResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let power = match height {
+        Some(height) => VOTING_POWER.may_load_at_height(deps.storage, &addr, height)?,
+        None => VOTING_POWER.may_load(deps.storage, &addr)?,
+    };
+    Ok(VotingPowerResponse {
+        power: power.unwrap_or_default(),
+    })
+}
+
+pub fn query_delegates(deps: Deps, address: String) -> StdResult<DelegatesResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let delegate = DELEGATES.may_load(deps.storage, &addr)?;
+    Ok(DelegatesResponse {
+        delegate: delegate.map(Addr::into_string),
+    })
+}
+
+pub fn query_nonce(deps: Deps, owner: String) -> StdResult<NonceResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    Ok(NonceResponse {
+        nonce: signature::query_nonce(deps.storage, &owner_addr)?,
+    })
+}
+
+pub fn query_vesting(deps: Deps, env: Env, address: String) -> StdResult<VestingResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let lockup = LOCKUPS.may_load(deps.storage, &addr)?;
+    Ok(VestingResponse {
+        amount: lockup.as_ref().map(|l| l.amount).unwrap_or_default(),
+        locked: lockup
+            .as_ref()
+            .map(|l| l.locked(&env.block))
+            .unwrap_or_default(),
+        schedule: lockup.map(|l| l.schedule),
+    })
+}
+
+/// The balance of address minus what is still locked
+pub fn query_spendable_balance(
+    deps: Deps,
+    env: Env,
+    address: String,
+) -> StdResult<BalanceResponse> {
+    let addr = deps.api.addr_validate(&address)?;
+    let balance = BALANCES.may_load(deps.storage, &addr)?.unwrap_or_default();
+    let locked = locked_balance(deps.storage, &env.block, &addr)?;
+    Ok(BalanceResponse {
+        balance: balance.saturating_sub(locked),
+    })
+}
+
+pub fn query_reserve(deps: Deps, env: Env) -> StdResult<ReserveResponse> {
+    let denom = TOKEN_INFO
+        .load(deps.storage)?
+        .native_denom
+        .ok_or_else(|| StdError::generic_err("Token does not wrap a native coin"))?;
+    let reserve = deps
+        .querier
+        .query_balance(env.contract.address, &denom)?
+        .amount;
+    let total_supply = TOTAL_SUPPLY.load(deps.storage)?;
+    Ok(ReserveResponse {
+        denom,
+        reserve,
+        total_supply,
+        surplus: reserve.saturating_sub(total_supply),
+    })
+}
+
+pub fn query_allowance(deps: Deps, owner: String, spender: String) -> StdResult<AllowanceResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let allowance = ALLOWANCES
+        .may_load(deps.storage, (&owner_addr, &spender_addr))?
+        .unwrap_or_default();
+    Ok(allowance)
+}
+
+pub fn query_minter(deps: Deps) -> StdResult<Option<MinterResponse>> {
+    let meta = TOKEN_INFO.load(deps.storage)?;
+    let minter = match meta.mint {
+        Some(m) => Some(MinterResponse {
+            minter: m.minter.into(),
+            cap: m.cap,
+        }),
+        None => None,
+    };
+    Ok(minter)
+}
+
+pub fn query_status(deps: Deps) -> StdResult<StatusResponse> {
+    Ok(StatusResponse {
+        paused: PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+        pauser: PAUSER.may_load(deps.storage)?.map(Addr::into_string),
+    })
+}
+
+pub fn query_allowlist(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllowlistResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|s| deps.api.addr_validate(&s))
+        .transpose()?;
+    let start = start.as_ref().map(Bound::exclusive);
+
+    let accounts = ALLOWLIST
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|addr| addr.map(Addr::into_string))
+        .collect::<StdResult<_>>()?;
+    Ok(AllowlistResponse { accounts })
+}
+
+pub fn query_frozen_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<FrozenAccountsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|s| deps.api.addr_validate(&s))
+        .transpose()?;
+    let start = start.as_ref().map(Bound::exclusive);
+
+    let accounts = FROZEN
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|addr| addr.map(Addr::into_string))
+        .collect::<StdResult<_>>()?;
+    Ok(FrozenAccountsResponse { accounts })
+}
+
+pub fn query_marketing_info(deps: Deps) -> StdResult<MarketingInfoResponse> {
+    Ok(MARKETING_INFO.may_load(deps.storage)?.unwrap_or_default())
+}
+
+pub fn query_download_logo(deps: Deps) -> StdResult<DownloadLogoResponse> {
+    let logo = LOGO.load(deps.storage)?;
+    match logo {
+        Logo::Embedded(EmbeddedLogo::Svg(data)) => Ok(DownloadLogoResponse {
+            mime_type: "image/svg+xml".to_owned(),
+            data,
+        }),
+        Logo::Embedded(EmbeddedLogo::Png(data)) => Ok(DownloadLogoResponse {
+            mime_type: "image/png".to_owned(),
+            data,
+        }),
+        Logo::Url(_) => Err(StdError::not_found("logo")),
+    }
+}
+
+pub fn query_all_allowances(
+    deps: Deps,
+    owner: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllAllowancesResponse> {
+    let owner_addr = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|s| deps.api.addr_validate(&s))
+        .transpose()?;
+
+    let allowances = ALLOWANCES
+        .prefix(&owner_addr)
+        .range(
+            deps.storage,
+            start.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            item.map(|(spender, allow)| AllowanceInfo {
+                spender: spender.into(),
+                allowance: allow.allowance,
+                expires: allow.expires,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(AllAllowancesResponse { allowances })
+}
+
+pub fn query_all_spender_allowances(
+    deps: Deps,
+    spender: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllSpenderAllowancesResponse> {
+    let spender_addr = deps.api.addr_validate(&spender)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|s| deps.api.addr_validate(&s))
+        .transpose()?;
+
+    let allowances = ALLOWANCES_SPENDER
+        .prefix(&spender_addr)
+        .range(
+            deps.storage,
+            start.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| {
+            item.map(|(owner, allow)| SpenderAllowanceInfo {
+                owner: owner.into(),
+                allowance: allow.allowance,
+                expires: allow.expires,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(AllSpenderAllowancesResponse { allowances })
+}
+
+pub fn query_all_accounts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AllAccountsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|s| deps.api.addr_validate(&s))
+        .transpose()?;
+
+    // read one extra entry to find out whether there is a next page. pages are cut before
+    // dropping zero balances, so spent accounts cannot make a page read more entries.
+    let mut entries = BALANCES
+        .range(
+            deps.storage,
+            start.as_ref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let next_start_after = if entries.len() > limit {
+        entries.truncate(limit);
+        entries.last().map(|(address, _)| address.to_string())
+    } else {
+        None
+    };
+    let accounts = entries
+        .into_iter()
+        // accounts that spent everything are no longer holders
+        .filter(|(_, balance)| !balance.is_zero())
+        .map(|(address, balance)| AccountBalance {
+            address: address.into(),
+            balance,
+        })
+        .collect();
+    Ok(AllAccountsResponse {
+        accounts,
+        next_start_after,
     })
 }
 
-pub fn query_balance(deps: Deps, owner: String) -> StdResult<BalanceResponse> {
-    let owner_addr = deps.api.addr_validate(&owner)?;
-    let balance = BALANCES
-        .may_load(deps.storage, &owner_addr)?
-        .unwrap_or_default();
-    Ok(BalanceResponse { balance })
-}
+// OnFTReceived
+pub fn query_on_ft_received(
+    _deps: Deps,
+    _token: String,
+    _from: String,
+    _operator: String,
+    amount: Uint128,
+    _msg: Option<Binary>,
+) -> StdResult<OnFTReceivedResponse> {
+    if amount == Uint128::zero() {
+        return Ok(OnFTReceivedResponse { enable: false });
+    }
+
+    Ok(OnFTReceivedResponse { enable: true })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use crate::event::{delegate_changed_event, ownership_transferred_event};
+    use crate::msg::{InitialBalance, InstantiateMarketingInfo, InstantiateMsg};
+    use bech32::{ToBase32, Variant};
+    use cosmwasm_std::testing::{
+        mock_dependencies_with_balance, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::to_vec;
+    use cosmwasm_std::{
+        from_binary, from_slice, ContractInfoResponse, ContractResult, CosmosMsg, OwnedDeps,
+        SubMsg, SystemError, SystemResult, WasmMsg, WasmQuery,
+    };
+    use cw_storage_plus::Map;
+    use cw_utils::PaymentError;
+    use k256::ecdsa::signature::Signer;
+    use k256::ecdsa::{Signature, SigningKey};
+    use ripemd::Ripemd160;
+    use sha2::{Digest, Sha256};
+
+    // registers `contract` as a smart contract that accepts tokens unless the payload is "reject"
+    pub(crate) fn mock_receiver(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        contract: &str,
+    ) {
+        mock_contracts(deps, &[contract], &[]);
+    }
+
+    // like mock_receiver, plus `plain` contracts which don't implement OnFTReceived
+    pub(crate) fn mock_contracts(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        receivers: &[&str],
+        plain: &[&str],
+    ) {
+        let receivers: Vec<String> = receivers.iter().map(|c| c.to_string()).collect();
+        let plain: Vec<String> = plain.iter().map(|c| c.to_string()).collect();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::ContractInfo { contract_addr }
+                if receivers.contains(contract_addr) || plain.contains(contract_addr) =>
+            {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&ContractInfoResponse::default()).unwrap(),
+                ))
+            }
+            WasmQuery::Smart { contract_addr, msg } if receivers.contains(contract_addr) => {
+                let enable = match from_binary(msg).unwrap() {
+                    QueryMsg::OnFTReceived { msg, .. } => msg != Some(Binary::from(b"reject")),
+                    _ => false,
+                };
+                SystemResult::Ok(ContractResult::Ok(
+                    to_binary(&OnFTReceivedResponse { enable }).unwrap(),
+                ))
+            }
+            WasmQuery::Smart { contract_addr, .. } if plain.contains(contract_addr) => {
+                SystemResult::Ok(ContractResult::Err("unknown variant".to_string()))
+            }
+            WasmQuery::ContractInfo { contract_addr } | WasmQuery::Smart { contract_addr, .. } => {
+                SystemResult::Err(SystemError::NoSuchContract {
+                    addr: contract_addr.clone(),
+                })
+            }
+            _ => SystemResult::Err(SystemError::Unknown {}),
+        });
+    }
+
+    fn do_instantiate(deps: DepsMut, creator: &str, amount: Uint128) -> InfoResponse {
+        _do_instantiate(deps, creator, amount, None)
+    }
+
+    fn do_instantiate_with_minter(
+        deps: DepsMut,
+        creator: &str,
+        amount: Uint128,
+        minter: &str,
+        cap: Option<Uint128>,
+    ) -> InfoResponse {
+        _do_instantiate(
+            deps,
+            creator,
+            amount,
+            Some(MinterResponse {
+                minter: minter.to_string(),
+                cap,
+            }),
+        )
+    }
+
+    /// A plain token with amount held by the creator. Tests set the fields they exercise
+    /// with struct update syntax.
+    pub(crate) fn instantiate_msg(amount: Uint128) -> InstantiateMsg {
+        InstantiateMsg {
+            name: "Test".to_string(),
+            symbol: "TST".to_string(),
+            decimals: 8,
+            initial_balances: InitialBalances::Amount(amount),
+            mint: None,
+            receiver_policy: None,
+            marketing: None,
+            allowlist: None,
+            native_denom: None,
+        }
+    }
+
+    fn _do_instantiate(
+        mut deps: DepsMut,
+        creator: &str,
+        amount: Uint128,
+        mint: Option<MinterResponse>,
+    ) -> InfoResponse {
+        let instantiate_msg = InstantiateMsg {
+            mint,
+            ..instantiate_msg(amount)
+        };
+        let info = mock_info(creator, &[]);
+        let env = mock_env();
+        let res = instantiate(deps.branch(), env, info, instantiate_msg).unwrap();
+        assert_eq!(0, res.messages.len());
+        query_info(deps.as_ref()).unwrap()
+    }
+
+    #[test]
+    fn transfer() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let creator = String::from("creator");
+        let recipient = String::from("recipient");
+
+        let init_balance = Uint128::new(1000000000);
+        do_instantiate(deps.as_mut(), &creator, init_balance);
+
+        // transfer zero
+        let msg = ExecuteMsg::Transfer {
+            recipient: recipient.clone(),
+            amount: Uint128::zero(),
+            on_receive_error: None,
+        };
+        let info = mock_info(creator.as_ref(), &[]);
+        let env = mock_env();
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidZeroAmount {});
+
+        // transfer normal
+        let amount = Uint128::new(1000);
+        let msg = ExecuteMsg::Transfer {
+            recipient: recipient.clone(),
+            amount,
+            on_receive_error: None,
+        };
+        let info = mock_info(creator.as_ref(), &[]);
+        let env = mock_env();
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(res.events[0].ty, "Transfer");
+    }
+
+    #[test]
+    fn transfer_from() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let spender = String::from("spender");
+        let recipient = String::from("recipient");
+
+        let init_balance = Uint128::new(1000000000);
+        do_instantiate(deps.as_mut(), &owner, init_balance);
+
+        // approve
+        let allow1 = Uint128::new(1000000);
+        let msg = ExecuteMsg::Approve {
+            spender: spender.clone(),
+            amount: allow1,
+            current_allowance: Uint128::zero(),
+            expires: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let env = mock_env();
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(res.events[0].ty, "Approval");
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        assert_eq!(allow1, allowance.allowance);
+
+        // transfer_from
+        let amount = Uint128::new(500000);
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: recipient.clone(),
+            amount,
+            on_receive_error: None,
+        };
+        let info = mock_info(spender.as_ref(), &[]);
+        let env = mock_env();
+        let res = execute(deps.as_mut(), env, info, msg).unwrap();
+        assert_eq!(res.events[0].ty, "Transfer");
+        let res = query_balance(deps.as_ref(), owner.clone()).unwrap();
+        assert_eq!(res.balance, init_balance - amount);
+    }
+
+    #[test]
+    fn mint() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let creator = String::from("creator");
+        let minter = String::from("minter");
+        let recipient = String::from("recipient");
+
+        let init_balance = Uint128::new(1000);
+        let cap = Uint128::new(3000);
+        do_instantiate_with_minter(deps.as_mut(), &creator, init_balance, &minter, Some(cap));
+        let res = query_minter(deps.as_ref()).unwrap().unwrap();
+        assert_eq!(res.minter, minter);
+        assert_eq!(res.cap, Some(cap));
+
+        // only minter can mint
+        let msg = ExecuteMsg::Mint {
+            recipient: recipient.clone(),
+            amount: Uint128::new(100),
+        };
+        let info = mock_info(creator.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // mint up to the cap
+        let amount = Uint128::new(2000);
+        let msg = ExecuteMsg::Mint {
+            recipient: recipient.clone(),
+            amount,
+        };
+        let info = mock_info(minter.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.events[0].ty, "Mint");
+        let res = query_balance(deps.as_ref(), recipient.clone()).unwrap();
+        assert_eq!(res.balance, amount);
+        let res = query_info(deps.as_ref()).unwrap();
+        assert_eq!(res.total_supply, cap);
+
+        // cannot exceed the cap
+        let msg = ExecuteMsg::Mint {
+            recipient,
+            amount: Uint128::new(1),
+        };
+        let info = mock_info(minter.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::CannotExceedCap {});
+    }
+
+    #[test]
+    fn burn() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let spender = String::from("spender");
+
+        let init_balance = Uint128::new(1000);
+        do_instantiate(deps.as_mut(), &owner, init_balance);
+
+        // burn more than the balance
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(1001),
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+
+        // burn normal
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(100),
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.events[0].ty, "Burn");
+
+        // burn_from without allowance
+        let msg = ExecuteMsg::BurnFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(100),
+        };
+        let info = mock_info(spender.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NoAllowance {});
+
+        // burn_from consumes the allowance
+        let msg = ExecuteMsg::Approve {
+            spender: spender.clone(),
+            amount: Uint128::new(300),
+            current_allowance: Uint128::zero(),
+            expires: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::BurnFrom {
+            owner: owner.clone(),
+            amount: Uint128::new(200),
+        };
+        let info = mock_info(spender.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.events[0].ty, "Burn");
+
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(100));
+        let res = query_balance(deps.as_ref(), owner).unwrap();
+        assert_eq!(res.balance, Uint128::new(700));
+        let res = query_info(deps.as_ref()).unwrap();
+        assert_eq!(res.total_supply, Uint128::new(700));
+    }
+
+    #[test]
+    fn migrate() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = Addr::unchecked("owner");
+        let spender = Addr::unchecked("spender");
+        do_instantiate(deps.as_mut(), owner.as_ref(), Uint128::new(1000));
+
+        // state as written by 0.1.0
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let legacy: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("alloance_spender");
+        let allowance = AllowanceResponse {
+            allowance: Uint128::new(100),
+            expires: Expiration::Never {},
+        };
+        legacy
+            .save(deps.as_mut().storage, (&spender, &owner), &allowance)
+            .unwrap();
+        ownership::OWNERSHIP.remove(deps.as_mut().storage);
+        PAUSER.remove(deps.as_mut().storage);
+        COMPLIANCE.remove(deps.as_mut().storage);
+        deps.storage.remove(b"total_supply");
+        deps.storage.set(
+            b"token_info",
+            br#"{"name":"Test","symbol":"TST","decimals":8,"total_supply":"1000"}"#,
+        );
+
+        // 0.1.x had no roles, they all go to the given owner
+        let admin = Addr::unchecked("admin");
+        let msg = MigrateMsg {
+            owner: Some(admin.to_string()),
+        };
+        let res = super::migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.attributes[1].value, "0.1.0");
+        let version = get_contract_version(deps.as_ref().storage).unwrap();
+        assert_eq!(version.version, CONTRACT_VERSION);
+        let moved = ALLOWANCES_SPENDER
+            .load(deps.as_ref().storage, (&spender, &owner))
+            .unwrap();
+        assert_eq!(moved, allowance);
+        assert!(!legacy.has(deps.as_ref().storage, (&spender, &owner)));
+        let ownership = ownership::get_ownership(deps.as_ref().storage).unwrap();
+        assert_eq!(ownership.owner, Some(admin.clone()));
+        assert_eq!(PAUSER.load(deps.as_ref().storage).unwrap(), admin);
+        assert_eq!(COMPLIANCE.load(deps.as_ref().storage).unwrap(), admin);
+        let info = query_info(deps.as_ref()).unwrap();
+        assert_eq!(info.total_supply, Uint128::new(1000));
+        assert_eq!(query_minter(deps.as_ref()).unwrap(), None);
+
+        // refuse downgrade
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "99.0.0").unwrap();
+        let err =
+            super::migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrateVersion {
+                previous_version: "99.0.0".to_string()
+            }
+        );
+
+        // refuse foreign contract
+        set_contract_version(deps.as_mut().storage, "crates.io:cw20-base", "0.1.0").unwrap();
+        let err =
+            super::migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::CannotMigrate {
+                previous_contract: "crates.io:cw20-base".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn allowance_expiration() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let spender = String::from("spender");
+        let recipient = String::from("recipient");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+
+        let env = mock_env();
+
+        // cannot approve with an already expired allowance
+        let msg = ExecuteMsg::Approve {
+            spender: spender.clone(),
+            amount: Uint128::new(100),
+            current_allowance: Uint128::zero(),
+            expires: Some(Expiration::AtHeight(env.block.height)),
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let err = execute(deps.as_mut(), env.clone(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidExpiration {});
+
+        let expires = Expiration::AtHeight(env.block.height + 10);
+        let msg = ExecuteMsg::Approve {
+            spender: spender.clone(),
+            amount: Uint128::new(100),
+            current_allowance: Uint128::zero(),
+            expires: Some(expires),
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        assert_eq!(allowance.expires, expires);
+
+        // spend before expiry
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: recipient.clone(),
+            amount: Uint128::new(10),
+            on_receive_error: None,
+        };
+        let info = mock_info(spender.as_ref(), &[]);
+        execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+        // rejected once expired
+        let mut env = env;
+        env.block.height += 10;
+        let msg = ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount: Uint128::new(10),
+            on_receive_error: None,
+        };
+        let info = mock_info(spender.as_ref(), &[]);
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Expired {});
+    }
+
+    #[test]
+    fn query_all_allowances_paginated() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let other_owner = String::from("other");
+        let spender1 = String::from("spender1");
+        let spender2 = String::from("spender2");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+
+        for (owner, spender, amount) in [
+            (&owner, &spender1, 100u128),
+            (&owner, &spender2, 200),
+            (&other_owner, &spender1, 300),
+        ] {
+            let msg = ExecuteMsg::Approve {
+                spender: spender.clone(),
+                amount: Uint128::new(amount),
+                current_allowance: Uint128::zero(),
+                expires: None,
+            };
+            let info = mock_info(owner.as_ref(), &[]);
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        // by owner, one page at a time
+        let res = query_all_allowances(deps.as_ref(), owner.clone(), None, Some(1)).unwrap();
+        assert_eq!(res.allowances.len(), 1);
+        assert_eq!(res.allowances[0].spender, spender1);
+        assert_eq!(res.allowances[0].allowance, Uint128::new(100));
+        let res = query_all_allowances(deps.as_ref(), owner.clone(), Some(spender1.clone()), None)
+            .unwrap();
+        assert_eq!(res.allowances.len(), 1);
+        assert_eq!(res.allowances[0].spender, spender2);
+
+        // by spender
+        let res = query_all_spender_allowances(deps.as_ref(), spender1, None, None).unwrap();
+        assert_eq!(res.allowances.len(), 2);
+        assert_eq!(res.allowances[0].owner, other_owner);
+        assert_eq!(res.allowances[1].owner, owner);
+        assert_eq!(res.allowances[1].allowance, Uint128::new(100));
+    }
+
+    #[test]
+    fn query_all_accounts_paginated() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let creator = String::from("creator");
+        do_instantiate(deps.as_mut(), &creator, Uint128::new(1000));
+
+        for recipient in ["carol", "alice", "bob"] {
+            let msg = ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: Uint128::new(100),
+                on_receive_error: None,
+            };
+            let info = mock_info(creator.as_ref(), &[]);
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+
+        let res = query_all_accounts(deps.as_ref(), None, Some(2)).unwrap();
+        let addresses: Vec<_> = res.accounts.iter().map(|a| a.address.as_str()).collect();
+        assert_eq!(addresses, vec!["alice", "bob"]);
+        assert_eq!(res.accounts[0].balance, Uint128::new(100));
+        assert_eq!(res.next_start_after, Some("bob".to_string()));
+
+        let res = query_all_accounts(deps.as_ref(), res.next_start_after, Some(2)).unwrap();
+        let addresses: Vec<_> = res.accounts.iter().map(|a| a.address.as_str()).collect();
+        assert_eq!(addresses, vec!["carol", "creator"]);
+        assert_eq!(res.accounts[1].balance, Uint128::new(700));
+        assert_eq!(res.next_start_after, None);
+
+        // spent accounts are skipped, but still count towards the page size
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), msg).unwrap();
+        let res = query_all_accounts(deps.as_ref(), None, Some(2)).unwrap();
+        let addresses: Vec<_> = res.accounts.iter().map(|a| a.address.as_str()).collect();
+        assert_eq!(addresses, vec!["bob"]);
+        assert_eq!(res.next_start_after, Some("bob".to_string()));
+    }
+
+    #[test]
+    fn increase_decrease_allowance() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let spender = String::from("spender");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+
+        // decrease without allowance
+        let msg = ExecuteMsg::DecreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(10),
+            expires: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NoAllowance {});
+
+        // increase twice
+        let expires = Expiration::AtHeight(123456);
+        for exp in [Some(expires), None] {
+            let msg = ExecuteMsg::IncreaseAllowance {
+                spender: spender.clone(),
+                amount: Uint128::new(100),
+                expires: exp,
+            };
+            let info = mock_info(owner.as_ref(), &[]);
+            execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        }
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(200));
+        assert_eq!(allowance.expires, expires);
+
+        // decrease reports old and new amounts
+        let msg = ExecuteMsg::DecreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(50),
+            expires: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.events[0].ty, "Approval");
+        assert_eq!(res.events[0].attributes[2].value, "200");
+        assert_eq!(res.events[0].attributes[3].value, "150");
+
+        // decreasing past zero removes the allowance
+        let msg = ExecuteMsg::DecreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(1000),
+            expires: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let res = query_all_spender_allowances(deps.as_ref(), spender, None, None).unwrap();
+        assert!(res.allowances.is_empty());
+    }
+
+    #[test]
+    fn send() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let contract = String::from("contract");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        mock_receiver(&mut deps, &contract);
+
+        // only contracts can be sent to
+        let msg = ExecuteMsg::Send {
+            contract: String::from("someone"),
+            amount: Uint128::new(100),
+            msg: Binary::from(b"deposit"),
+            on_receive_error: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NotContract {});
+
+        // the receiver can refuse based on the payload
+        let msg = ExecuteMsg::Send {
+            contract: contract.clone(),
+            amount: Uint128::new(100),
+            msg: Binary::from(b"reject"),
+            on_receive_error: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NonTransferable {});
+
+        // the payload and the owner are forwarded to the receiver
+        let msg = ExecuteMsg::Send {
+            contract: contract.clone(),
+            amount: Uint128::new(100),
+            msg: Binary::from(b"deposit"),
+            on_receive_error: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let env = mock_env();
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let expected = ExecuteMsg::Receive {
+            token: env.contract.address.to_string(),
+            from: owner.clone(),
+            operator: owner,
+            amount: Uint128::new(100),
+            msg: Some(Binary::from(b"deposit")),
+        };
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract.clone(),
+                msg: to_binary(&expected).unwrap(),
+                funds: vec![],
+            })
+        );
+        let res = query_balance(deps.as_ref(), contract).unwrap();
+        assert_eq!(res.balance, Uint128::new(100));
+    }
+
+    #[test]
+    fn transfer_from_contract_hook() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let spender = String::from("spender");
+        let contract = String::from("contract");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        mock_receiver(&mut deps, &contract);
+
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(100),
+            expires: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+        // the hook credits the owner and names the spender as operator
+        let msg = ExecuteMsg::TransferFrom {
+            owner: owner.clone(),
+            recipient: contract.clone(),
+            amount: Uint128::new(100),
+            on_receive_error: None,
+        };
+        let info = mock_info(spender.as_ref(), &[]);
+        let env = mock_env();
+        let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+        let expected = ExecuteMsg::Receive {
+            token: env.contract.address.to_string(),
+            from: owner,
+            operator: spender,
+            amount: Uint128::new(100),
+            msg: None,
+        };
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract,
+                msg: to_binary(&expected).unwrap(),
+                funds: vec![],
+            })
+        );
+    }
+
+    #[test]
+    fn receive_hook_failure() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let contract = String::from("contract");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        mock_receiver(&mut deps, &contract);
+
+        let failed = |id| Reply {
+            id,
+            result: SubMsgResult::Err("not today".to_string()),
+        };
+
+        // fail: the error names the receiver and its reason
+        let msg = ExecuteMsg::Transfer {
+            recipient: contract.clone(),
+            amount: Uint128::new(100),
+            on_receive_error: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let err = reply(deps.as_mut(), mock_env(), failed(res.messages[0].id)).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::ReceiverRejected {
+                contract: contract.clone(),
+                reason: "not today".to_string(),
+            }
+        );
+
+        // revert: the tokens go back to the owner
+        let msg = ExecuteMsg::Transfer {
+            recipient: contract.clone(),
+            amount: Uint128::new(100),
+            on_receive_error: Some(OnReceiveError::Revert),
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let id = res.messages[0].id;
+        let res = reply(deps.as_mut(), mock_env(), failed(id)).unwrap();
+        assert_eq!(res.events[0].ty, "TransferReverted");
+        let res = query_balance(deps.as_ref(), contract).unwrap();
+        // the first transfer was not rolled back in this unit test
+        assert_eq!(res.balance, Uint128::new(100));
+        assert!(!PENDING_RECEIVES.has(deps.as_ref().storage, id));
+    }
+
+    #[test]
+    fn receiver_policy() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let receiver = String::from("receiver");
+        let plain = String::from("plain");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        mock_contracts(&mut deps, &[&receiver], &[&plain]);
+
+        let transfer = |recipient: &str| ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(100),
+            on_receive_error: None,
+        };
+        let update = |policy| ExecuteMsg::UpdateReceiverPolicy { policy };
+
+        // strict: contracts must implement OnFTReceived
+        let info = mock_info(owner.as_ref(), &[]);
+        execute(deps.as_mut(), mock_env(), info, transfer(&plain)).unwrap_err();
+
+        // only the admin can change the policy
+        let info = mock_info(receiver.as_ref(), &[]);
+        let msg = update(ReceiverPolicy::Lenient);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // lenient: contracts without OnFTReceived get the tokens without the hook
+        let info = mock_info(owner.as_ref(), &[]);
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            info,
+            update(ReceiverPolicy::Lenient),
+        )
+        .unwrap();
+        let info = mock_info(owner.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, transfer(&plain)).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(res.attributes[0].value, "lenient");
+        assert_eq!(res.attributes[1].value, "false");
+        let info = mock_info(owner.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, transfer(&receiver)).unwrap();
+        assert_eq!(res.messages.len(), 1);
+
+        // off: hooks are never called
+        let info = mock_info(owner.as_ref(), &[]);
+        execute(deps.as_mut(), mock_env(), info, update(ReceiverPolicy::Off)).unwrap();
+        let info = mock_info(owner.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, transfer(&receiver)).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(res.attributes[0].value, "off");
+        assert_eq!(
+            query_info(deps.as_ref()).unwrap().receiver_policy,
+            ReceiverPolicy::Off
+        );
+    }
+
+    #[test]
+    fn batch_transfer() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let spender = String::from("spender");
+        let contract = String::from("contract");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        mock_receiver(&mut deps, &contract);
+
+        let item = |recipient: &str, amount: u128| TransferItem {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+        };
+
+        // an invalid leg fails the whole batch with its index
+        let msg = ExecuteMsg::BatchTransfer {
+            transfers: vec![item("alice", 100), item("bob", 0)],
+            on_receive_error: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidBatchLeg {
+                index: 1,
+                error: Box::new(ContractError::InvalidZeroAmount {}),
+            }
+        );
+
+        // one event per leg, hooks for contract recipients
+        let msg = ExecuteMsg::BatchTransfer {
+            transfers: vec![item("alice", 100), item(&contract, 200)],
+            on_receive_error: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(res.events.len(), 2);
+        assert_eq!(res.messages.len(), 1);
+        let balance =
+            |deps: Deps, address: &str| query_balance(deps, address.to_string()).unwrap().balance;
+        assert_eq!(balance(deps.as_ref(), &owner), Uint128::new(700));
+        assert_eq!(balance(deps.as_ref(), &contract), Uint128::new(200));
+
+        // batch_transfer_from spends the allowance for the whole batch
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(250),
+            expires: None,
+        };
+        let info = mock_info(owner.as_ref(), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let msg = ExecuteMsg::BatchTransferFrom {
+            owner: owner.clone(),
+            transfers: vec![item("alice", 100), item("bob", 100)],
+            on_receive_error: None,
+        };
+        let info = mock_info(spender.as_ref(), &[]);
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(50));
+        assert_eq!(balance(deps.as_ref(), "alice"), Uint128::new(200));
+        assert_eq!(balance(deps.as_ref(), &owner), Uint128::new(500));
+
+        // like TransferFrom, the owner needs an allowance of its own
+        let msg = ExecuteMsg::BatchTransferFrom {
+            owner: owner.clone(),
+            transfers: vec![item("alice", 10)],
+            on_receive_error: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoAllowance {});
+    }
 
-pub fn query_allowance(deps: Deps, owner: String, spender: String) -> StdResult<AllowanceResponse> {
-    let owner_addr = deps.api.addr_validate(&owner)?;
-    let spender_addr = deps.api.addr_validate(&spender)?;
-    let allowance = ALLOWANCES
-        .may_load(deps.storage, (&owner_addr, &spender_addr))?
-        .unwrap_or_default();
-    Ok(allowance)
-}
+    #[test]
+    fn instantiate_initial_balances() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let creator = String::from("creator");
 
-// OnFTReceived
-pub fn query_on_ft_received(
-    _deps: Deps,
-    _sender: String,
-    _owner: String,
-    amount: Uint128,
-) -> StdResult<OnFTReceivedResponse> {
-    if amount == Uint128::zero() {
-        return Ok(OnFTReceivedResponse { enable: false });
+        let msg: InstantiateMsg = from_slice(
+            br#"{"name":"Test","symbol":"TST","decimals":8,"mint":null,"receiver_policy":null,
+            "initial_balances":[{"address":"alice","amount":"100"},{"address":"bob","amount":"200"}]}"#,
+        )
+        .unwrap();
+        let res = instantiate(deps.as_mut(), mock_env(), mock_info(&creator, &[]), msg).unwrap();
+        assert_eq!(
+            res.events,
+            vec![
+                mint_event(&creator, "alice", Uint128::new(100)),
+                mint_event(&creator, "bob", Uint128::new(200)),
+            ]
+        );
+        let info = query_info(deps.as_ref()).unwrap();
+        assert_eq!(info.total_supply, Uint128::new(300));
+        let balance = query_balance(deps.as_ref(), "bob".to_string()).unwrap();
+        assert_eq!(balance.balance, Uint128::new(200));
+        let balance = query_balance(deps.as_ref(), creator.clone()).unwrap();
+        assert_eq!(balance.balance, Uint128::zero());
+
+        // the scalar form is still accepted
+        let msg: InstantiateMsg = from_slice(
+            br#"{"name":"Test","symbol":"TST","decimals":8,"mint":null,"receiver_policy":null,
+            "initial_balances":"1000"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            msg.initial_balances,
+            InitialBalances::Amount(Uint128::new(1000))
+        );
+
+        // duplicated addresses
+        let mut msg = msg;
+        msg.initial_balances = InitialBalances::Accounts(vec![
+            InitialBalance {
+                address: "alice".to_string(),
+                amount: Uint128::new(100),
+            },
+            InitialBalance {
+                address: "alice".to_string(),
+                amount: Uint128::new(200),
+            },
+        ]);
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&creator, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::DuplicateInitialBalanceAddresses {});
+
+        // overflow
+        msg.initial_balances = InitialBalances::Accounts(vec![
+            InitialBalance {
+                address: "alice".to_string(),
+                amount: Uint128::MAX,
+            },
+            InitialBalance {
+                address: "bob".to_string(),
+                amount: Uint128::new(1),
+            },
+        ]);
+        let err =
+            instantiate(deps.as_mut(), mock_env(), mock_info(&creator, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::Std(StdError::Overflow { .. })));
     }
 
-    Ok(OnFTReceivedResponse { enable: true })
-}
+    #[test]
+    fn marketing() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let creator = String::from("creator");
+        let marketing = String::from("marketing");
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let msg = InstantiateMsg {
+            marketing: Some(InstantiateMarketingInfo {
+                project: Some("https://example.com".to_string()),
+                description: Some("Test token".to_string()),
+                marketing: Some(marketing.clone()),
+                logo: Some(Logo::Url("https://example.com/logo.png".to_string())),
+            }),
+            ..instantiate_msg(Uint128::new(1000))
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info(&creator, &[]), msg).unwrap();
+        let res = query_marketing_info(deps.as_ref()).unwrap();
+        assert_eq!(
+            res,
+            MarketingInfoResponse {
+                project: Some("https://example.com".to_string()),
+                description: Some("Test token".to_string()),
+                marketing: Some(Addr::unchecked(&marketing)),
+                logo: Some(LogoInfo::Url("https://example.com/logo.png".to_string())),
+            }
+        );
+        // url logos are not served by DownloadLogo
+        query_download_logo(deps.as_ref()).unwrap_err();
 
-    use crate::msg::InstantiateMsg;
-    use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
+        // only the marketing address can update
+        let msg = ExecuteMsg::UpdateMarketing {
+            project: None,
+            description: Some("".to_string()),
+            marketing: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&creator, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info(&marketing, &[]), msg).unwrap();
+        let res = query_marketing_info(deps.as_ref()).unwrap();
+        assert_eq!(res.description, None);
+        assert_eq!(res.project, Some("https://example.com".to_string()));
 
-    fn do_instantiate(mut deps: DepsMut, creator: &str, amount: Uint128) -> InfoResponse {
-        let instantiate_msg = InstantiateMsg {
-            name: "Test".to_string(),
-            symbol: "TST".to_string(),
-            decimals: 8,
-            initial_balances: amount,
+        // invalid headers are refused
+        let msg = ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Png(b"not a png".into())));
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&marketing, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidPngHeader {});
+        let msg = ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Svg(b"<html/>".into())));
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&marketing, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidXmlPreamble {});
+        let mut svg = b"<svg>".to_vec();
+        svg.resize(LOGO_SIZE_CAP + 1, b' ');
+        let msg = ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Svg(svg.into())));
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&marketing, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::LogoTooBig {});
+
+        // embedded logos can be downloaded
+        let svg = Binary::from(b"<?xml version=\"1.0\"?><svg></svg>".as_slice());
+        let msg = ExecuteMsg::UploadLogo(Logo::Embedded(EmbeddedLogo::Svg(svg.clone())));
+        execute(deps.as_mut(), mock_env(), mock_info(&marketing, &[]), msg).unwrap();
+        let res = query_marketing_info(deps.as_ref()).unwrap();
+        assert_eq!(res.logo, Some(LogoInfo::Embedded));
+        let res = query_download_logo(deps.as_ref()).unwrap();
+        assert_eq!(res.mime_type, "image/svg+xml");
+        assert_eq!(res.data, svg);
+    }
+
+    #[test]
+    fn two_step_ownership() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let new_owner = String::from("new_owner");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+
+        let ownership = ownership::get_ownership(deps.as_ref().storage).unwrap();
+        assert_eq!(ownership.owner, Some(Addr::unchecked(&owner)));
+
+        // only the owner can start a transfer
+        let msg = ExecuteMsg::TransferOwnership {
+            new_owner: new_owner.clone(),
+            expiry: Some(Expiration::AtHeight(mock_env().block.height + 10)),
         };
-        let info = mock_info(creator, &[]);
-        let env = mock_env();
-        let res = instantiate(deps.branch(), env, info, instantiate_msg).unwrap();
-        assert_eq!(0, res.messages.len());
-        query_info(deps.as_ref()).unwrap()
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&new_owner, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        assert_eq!(res.events[0].ty, "OwnershipTransferStarted");
+
+        // the owner does not change until the transfer is accepted
+        let msg = ExecuteMsg::UpdateReceiverPolicy {
+            policy: ReceiverPolicy::Off,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&new_owner, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // only the pending owner can accept, before the expiry
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let mut env = mock_env();
+        env.block.height += 10;
+        let err = execute(
+            deps.as_mut(),
+            env,
+            mock_info(&new_owner, &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::OwnershipTransferExpired {});
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&new_owner, &[]),
+            ExecuteMsg::AcceptOwnership {},
+        )
+        .unwrap();
+        assert_eq!(
+            res.events,
+            vec![ownership_transferred_event(&owner, &new_owner)]
+        );
+        let ownership = ownership::get_ownership(deps.as_ref().storage).unwrap();
+        assert_eq!(ownership.owner, Some(Addr::unchecked(&new_owner)));
+        assert_eq!(ownership.pending_owner, None);
+
+        // renouncing leaves the token without owner
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&new_owner, &[]),
+            ExecuteMsg::RenounceOwnership {},
+        )
+        .unwrap();
+        let ownership = ownership::get_ownership(deps.as_ref().storage).unwrap();
+        assert_eq!(ownership, ownership::Ownership::default());
+        let msg = ExecuteMsg::UpdateReceiverPolicy {
+            policy: ReceiverPolicy::Off,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&new_owner, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
     }
 
     #[test]
-    fn transfer() {
+    fn pause() {
         let mut deps = mock_dependencies_with_balance(&[]);
-        let creator = String::from("creator");
-        let recipient = String::from("recipient");
+        let owner = String::from("owner");
+        let pauser = String::from("pauser");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
 
-        let init_balance = Uint128::new(1000000000);
-        do_instantiate(deps.as_mut(), &creator, init_balance);
+        // the owner holds the pauser role until it hands it over
+        let msg = ExecuteMsg::UpdatePauser {
+            new_pauser: Some(pauser.clone()),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&pauser, &[]),
+            ExecuteMsg::Pause {},
+        )
+        .unwrap();
+        assert_eq!(
+            query_status(deps.as_ref()).unwrap(),
+            StatusResponse {
+                paused: true,
+                pauser: Some(pauser.clone()),
+            }
+        );
 
-        // transfer zero
+        // transfers and approvals are stopped
+        let msg = ExecuteMsg::Transfer {
+            recipient: "recipient".to_string(),
+            amount: Uint128::new(100),
+            on_receive_error: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        let approve = ExecuteMsg::IncreaseAllowance {
+            spender: "spender".to_string(),
+            amount: Uint128::new(100),
+            expires: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), approve).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+        let burn = ExecuteMsg::Burn {
+            amount: Uint128::new(100),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), burn).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&pauser, &[]),
+            ExecuteMsg::Unpause {},
+        )
+        .unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let balance = query_balance(deps.as_ref(), "recipient".to_string()).unwrap();
+        assert_eq!(balance.balance, Uint128::new(100));
+    }
+
+    #[test]
+    fn freeze() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let frozen = String::from("frozen");
+        let spender = String::from("spender");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+
+        // frozen holds tokens and approved spender before the freeze
+        let msg = ExecuteMsg::Transfer {
+            recipient: frozen.clone(),
+            amount: Uint128::new(200),
+            on_receive_error: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: spender.clone(),
+            amount: Uint128::new(100),
+            expires: None,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&frozen, &[]), msg).unwrap();
+
+        // only the compliance role freezes accounts
+        let msg = ExecuteMsg::Freeze {
+            address: frozen.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&frozen, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let res = query_frozen_accounts(deps.as_ref(), None, None).unwrap();
+        assert_eq!(res.accounts, vec![frozen.clone()]);
+        let res = query_frozen_accounts(deps.as_ref(), Some(frozen.clone()), None).unwrap();
+        assert!(res.accounts.is_empty());
+        query_frozen_accounts(deps.as_ref(), Some("Frozen".to_string()), None).unwrap_err();
+
+        // frozen accounts can neither receive nor be approved
+        let transfer = ExecuteMsg::Transfer {
+            recipient: frozen.clone(),
+            amount: Uint128::new(100),
+            on_receive_error: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            transfer.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AccountFrozen {
+                address: frozen.clone()
+            }
+        );
+        let msg = ExecuteMsg::IncreaseAllowance {
+            spender: frozen.clone(),
+            amount: Uint128::new(100),
+            expires: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AccountFrozen {
+                address: frozen.clone()
+            }
+        );
+
+        // their tokens cannot be burned, neither by them nor through an earlier allowance
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(50),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&frozen, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AccountFrozen {
+                address: frozen.clone()
+            }
+        );
+        let msg = ExecuteMsg::BurnFrom {
+            owner: frozen.clone(),
+            amount: Uint128::new(50),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&spender, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::AccountFrozen {
+                address: frozen.clone()
+            }
+        );
+        let balance = query_balance(deps.as_ref(), frozen.clone()).unwrap();
+        assert_eq!(balance.balance, Uint128::new(200));
+
+        let msg = ExecuteMsg::Unfreeze {
+            address: frozen.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), transfer).unwrap();
+        let res = query_frozen_accounts(deps.as_ref(), None, None).unwrap();
+        assert!(res.accounts.is_empty());
+    }
+
+    #[test]
+    fn allowlist() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let alice = String::from("alice");
+        let contract = String::from("contract");
+        mock_receiver(&mut deps, &contract);
+
+        let mut msg = InstantiateMsg {
+            allowlist: Some(vec![alice.clone()]),
+            ..instantiate_msg(Uint128::new(1000))
+        };
+        // genesis balances must go to allowlisted accounts
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotAllowlisted {
+                address: owner.clone()
+            }
+        );
+        msg.allowlist = Some(vec![owner.clone(), alice.clone()]);
+        instantiate(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        assert!(query_info(deps.as_ref()).unwrap().allowlist_only);
+
+        let transfer = |recipient: &str| ExecuteMsg::Transfer {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(100),
+            on_receive_error: None,
+        };
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            transfer(&alice),
+        )
+        .unwrap();
+
+        // contract recipients are no exception
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            transfer(&contract),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::NotAllowlisted {
+                address: contract.clone()
+            }
+        );
+        let msg = ExecuteMsg::AddToAllowlist {
+            address: contract.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&alice, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            transfer(&contract),
+        )
+        .unwrap();
+        let res = query_allowlist(deps.as_ref(), None, None).unwrap();
+        assert_eq!(
+            res.accounts,
+            vec![alice.clone(), contract.clone(), owner.clone()]
+        );
+        let res = query_allowlist(deps.as_ref(), Some(alice.clone()), Some(1)).unwrap();
+        assert_eq!(res.accounts, vec![contract.clone()]);
+        query_allowlist(deps.as_ref(), Some("Alice".to_string()), None).unwrap_err();
+
+        // removed accounts keep their balance but cannot receive more
+        let msg = ExecuteMsg::RemoveFromAllowlist {
+            address: alice.clone(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            transfer(&alice),
+        )
+        .unwrap_err();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&alice, &[]),
+            transfer(&owner),
+        )
+        .unwrap();
+
+        // batches report the leg paying an account off the allowlist
+        let msg = ExecuteMsg::BatchTransfer {
+            transfers: vec![
+                TransferItem {
+                    recipient: contract.clone(),
+                    amount: Uint128::new(10),
+                },
+                TransferItem {
+                    recipient: "bob".to_string(),
+                    amount: Uint128::new(10),
+                },
+            ],
+            on_receive_error: None,
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InvalidBatchLeg {
+                index: 1,
+                error: Box::new(ContractError::NotAllowlisted {
+                    address: "bob".to_string()
+                }),
+            }
+        );
+
+        // without allowlist mode anyone can receive
+        let msg = ExecuteMsg::UpdateAllowlistMode {
+            allowlist_only: false,
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            transfer(&alice),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn snapshots() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let recipient = String::from("recipient");
+        let minter = String::from("minter");
+        do_instantiate_with_minter(deps.as_mut(), &owner, Uint128::new(1000), &minter, None);
+        let start = mock_env().block.height;
+
+        let mut env = mock_env();
+        env.block.height = start + 1;
         let msg = ExecuteMsg::Transfer {
             recipient: recipient.clone(),
-            amount: Uint128::zero(),
+            amount: Uint128::new(300),
+            on_receive_error: None,
         };
-        let info = mock_info(creator.as_ref(), &[]);
-        let env = mock_env();
-        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
-        assert_eq!(err, ContractError::InvalidZeroAmount {});
+        execute(deps.as_mut(), env.clone(), mock_info(&owner, &[]), msg).unwrap();
 
-        // transfer normal
-        let amount = Uint128::new(1000);
+        env.block.height = start + 2;
+        let msg = ExecuteMsg::Mint {
+            recipient: recipient.clone(),
+            amount: Uint128::new(500),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&minter, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(100),
+        };
+        execute(deps.as_mut(), env, mock_info(&owner, &[]), msg).unwrap();
+
+        // heights read the state at the beginning of the block
+        let balance_at = |deps: Deps, address: &str, height: u64| {
+            query_balance_at_height(deps, address.to_string(), height)
+                .unwrap()
+                .balance
+        };
+        let supply_at = |deps: Deps, height: u64| {
+            query_total_supply_at_height(deps, height)
+                .unwrap()
+                .total_supply
+        };
+        assert_eq!(
+            balance_at(deps.as_ref(), &owner, start + 1),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &recipient, start + 1),
+            Uint128::zero()
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &owner, start + 2),
+            Uint128::new(700)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &recipient, start + 2),
+            Uint128::new(300)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &owner, start + 3),
+            Uint128::new(600)
+        );
+        assert_eq!(
+            balance_at(deps.as_ref(), &recipient, start + 3),
+            Uint128::new(800)
+        );
+        assert_eq!(supply_at(deps.as_ref(), start + 1), Uint128::new(1000));
+        assert_eq!(supply_at(deps.as_ref(), start + 2), Uint128::new(1000));
+        assert_eq!(supply_at(deps.as_ref(), start + 3), Uint128::new(1400));
+    }
+
+    #[test]
+    fn delegation() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let voter = String::from("voter");
+        let recipient = String::from("recipient");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        let start = mock_env().block.height;
+        let power = |deps: Deps, address: &str, height: Option<u64>| {
+            query_voting_power(deps, address.to_string(), height)
+                .unwrap()
+                .power
+        };
+
+        // balances count only once delegated
+        assert_eq!(power(deps.as_ref(), &owner, None), Uint128::zero());
+        let mut env = mock_env();
+        env.block.height = start + 1;
+        let msg = ExecuteMsg::Delegate {
+            delegatee: voter.clone(),
+        };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(&owner, &[]), msg).unwrap();
+        assert_eq!(res.events, vec![delegate_changed_event(&owner, "", &voter)]);
+        assert_eq!(power(deps.as_ref(), &voter, None), Uint128::new(1000));
+        let delegates = query_delegates(deps.as_ref(), owner.clone()).unwrap();
+        assert_eq!(delegates.delegate, Some(voter.clone()));
+
+        // transfers move the delegated power along
+        env.block.height = start + 2;
         let msg = ExecuteMsg::Transfer {
             recipient: recipient.clone(),
-            amount,
+            amount: Uint128::new(400),
+            on_receive_error: None,
         };
-        let info = mock_info(creator.as_ref(), &[]);
+        execute(deps.as_mut(), env.clone(), mock_info(&owner, &[]), msg).unwrap();
+        assert_eq!(power(deps.as_ref(), &voter, None), Uint128::new(600));
+        let msg = ExecuteMsg::Delegate {
+            delegatee: voter.clone(),
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(&recipient, &[]), msg).unwrap();
+        assert_eq!(power(deps.as_ref(), &voter, None), Uint128::new(1000));
+
+        // redelegating to self
+        env.block.height = start + 3;
+        let msg = ExecuteMsg::Delegate {
+            delegatee: owner.clone(),
+        };
+        execute(deps.as_mut(), env, mock_info(&owner, &[]), msg).unwrap();
+        assert_eq!(power(deps.as_ref(), &owner, None), Uint128::new(600));
+        assert_eq!(power(deps.as_ref(), &voter, None), Uint128::new(400));
+
+        // checkpoints
+        assert_eq!(
+            power(deps.as_ref(), &voter, Some(start + 1)),
+            Uint128::zero()
+        );
+        assert_eq!(
+            power(deps.as_ref(), &voter, Some(start + 2)),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            power(deps.as_ref(), &voter, Some(start + 3)),
+            Uint128::new(1000)
+        );
+        assert_eq!(
+            power(deps.as_ref(), &owner, Some(start + 3)),
+            Uint128::zero()
+        );
+    }
+
+    // a secp256k1 key with its pubkey and cosmos address
+    fn signer(seed: u8) -> (SigningKey, Binary, String) {
+        let key = SigningKey::from_bytes(&[seed; 32]).unwrap();
+        let pubkey = key.verifying_key().to_bytes().to_vec();
+        let hash = Ripemd160::digest(Sha256::digest(&pubkey));
+        let address = bech32::encode("cosmos", hash.to_base32(), Variant::Bech32).unwrap();
+        (key, pubkey.into(), address)
+    }
+
+    fn sign<T: serde::Serialize>(key: &SigningKey, sign_doc: &T) -> Binary {
+        let signature: Signature = key.sign(&to_vec(sign_doc).unwrap());
+        signature.as_ref().to_vec().into()
+    }
+
+    #[test]
+    fn permit() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let (key, pubkey, owner) = signer(1);
+        let (_, other_pubkey, _) = signer(2);
+        let spender = String::from("spender");
+        let relayer = String::from("relayer");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+
         let env = mock_env();
-        let res = execute(deps.as_mut(), env, info, msg).unwrap();
-        assert_eq!(res.events[0].ty, "Transfer");
+        let sign_doc = PermitSignDoc {
+            chain_id: env.block.chain_id.clone(),
+            contract: env.contract.address.to_string(),
+            owner: owner.clone(),
+            spender: spender.clone(),
+            amount: Uint128::new(300),
+            expires: None,
+            nonce: 0,
+        };
+        let permit =
+            |sign_doc: &PermitSignDoc, pubkey: &Binary, signature: Binary| ExecuteMsg::Permit {
+                owner: sign_doc.owner.clone(),
+                spender: sign_doc.spender.clone(),
+                amount: sign_doc.amount,
+                expires: sign_doc.expires,
+                nonce: sign_doc.nonce,
+                pubkey: pubkey.clone(),
+                signature,
+            };
+
+        // the pubkey must be the owner's
+        let msg = permit(&sign_doc, &other_pubkey, sign(&key, &sign_doc));
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&relayer, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::PubkeyMismatch {});
+
+        // the signature must cover the submitted values
+        let mut tampered = sign_doc.clone();
+        tampered.amount = Uint128::new(1000);
+        let msg = permit(&tampered, &pubkey, sign(&key, &sign_doc));
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&relayer, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSignature {});
+
+        // anyone can submit a valid permit
+        let msg = permit(&sign_doc, &pubkey, sign(&key, &sign_doc));
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&relayer, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(
+            res.events,
+            vec![approval_event(
+                &owner,
+                &spender,
+                Uint128::zero(),
+                Uint128::new(300)
+            )]
+        );
+        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
+        assert_eq!(allowance.allowance, Uint128::new(300));
+        let res = query_all_spender_allowances(deps.as_ref(), spender, None, None).unwrap();
+        assert_eq!(res.allowances.len(), 1);
+        assert_eq!(query_nonce(deps.as_ref(), owner).unwrap().nonce, 1);
+
+        // a permit can be used once
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&relayer, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidNonce { expected: 1 });
     }
 
     #[test]
-    fn transfer_from() {
+    fn relayed_transfer() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let (key, pubkey, from) = signer(1);
+        let relayer = String::from("relayer");
+        let contract = String::from("contract");
+        do_instantiate(deps.as_mut(), &from, Uint128::new(1000));
+        mock_receiver(&mut deps, &contract);
+
+        let env = mock_env();
+        let sign_doc = RelayedTransferSignDoc {
+            chain_id: env.block.chain_id.clone(),
+            contract: env.contract.address.to_string(),
+            from: from.clone(),
+            recipient: contract.clone(),
+            amount: Uint128::new(300),
+            fee: Uint128::new(10),
+            nonce: 0,
+            deadline: Expiration::AtHeight(env.block.height + 10),
+            relayer: Some(relayer.clone()),
+        };
+        let relayed = |sign_doc: &RelayedTransferSignDoc| ExecuteMsg::RelayedTransfer {
+            from: sign_doc.from.clone(),
+            recipient: sign_doc.recipient.clone(),
+            amount: sign_doc.amount,
+            fee: sign_doc.fee,
+            nonce: sign_doc.nonce,
+            deadline: sign_doc.deadline,
+            relayer: sign_doc.relayer.clone(),
+            pubkey: pubkey.clone(),
+            signature: sign(&key, sign_doc),
+        };
+
+        // too late to relay
+        let mut late = mock_env();
+        late.block.height += 10;
+        let err = execute(
+            deps.as_mut(),
+            late,
+            mock_info(&relayer, &[]),
+            relayed(&sign_doc),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::SignatureExpired {});
+
+        // only the chosen relayer can submit it
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("front-runner", &[]),
+            relayed(&sign_doc),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::WrongRelayer {
+                relayer: relayer.clone()
+            }
+        );
+
+        // the recipient contract gets its hook, the relayer its fee
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&relayer, &[]),
+            relayed(&sign_doc),
+        )
+        .unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract.clone(),
+                msg: to_binary(&ExecuteMsg::Receive {
+                    token: env.contract.address.to_string(),
+                    from: from.clone(),
+                    operator: from.clone(),
+                    amount: Uint128::new(300),
+                    msg: None,
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+        let balance =
+            |deps: Deps, address: &str| query_balance(deps, address.to_string()).unwrap().balance;
+        assert_eq!(balance(deps.as_ref(), &from), Uint128::new(690));
+        assert_eq!(balance(deps.as_ref(), &contract), Uint128::new(300));
+        assert_eq!(balance(deps.as_ref(), &relayer), Uint128::new(10));
+
+        // no replay
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&relayer, &[]),
+            relayed(&sign_doc),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::InvalidNonce { expected: 1 });
+    }
+
+    #[test]
+    fn wrapped_native() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let creator = String::from("creator");
+        let user = String::from("user");
+
+        let mut msg = InstantiateMsg {
+            native_denom: Some("unat".to_string()),
+            ..instantiate_msg(Uint128::new(100))
+        };
+        // the initial supply must be backed
+        let err = instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&creator, &coins(50, "unat")),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::InitialSupplyNotFunded {
+                expected: Uint128::new(100)
+            }
+        );
+        msg.mint = Some(MinterResponse {
+            minter: creator.clone(),
+            cap: None,
+        });
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&creator, &coins(100, "unat")),
+            msg.clone(),
+        )
+        .unwrap_err();
+        msg.mint = None;
+        instantiate(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&creator, &coins(100, "unat")),
+            msg,
+        )
+        .unwrap();
+
+        // deposits mint 1:1
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&user, &coins(300, "other")),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::Payment(PaymentError::MissingDenom("unat".to_string()))
+        );
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&user, &coins(300, "unat")),
+            ExecuteMsg::Deposit {},
+        )
+        .unwrap();
+        let balance = query_balance(deps.as_ref(), user.clone()).unwrap();
+        assert_eq!(balance.balance, Uint128::new(300));
+
+        // withdrawals burn and send the coins back, burning alone is not possible
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(100),
+        };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&user, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::WrappedNativeBurn {});
+        let msg = ExecuteMsg::Withdraw {
+            amount: Uint128::new(120),
+        };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&user, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages,
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: user.clone(),
+                amount: coins(120, "unat"),
+            })]
+        );
+        let balance = query_balance(deps.as_ref(), user).unwrap();
+        assert_eq!(balance.balance, Uint128::new(180));
+
+        // the mock bank does not follow the messages, set what the chain would hold
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(280, "unat"));
+        assert_eq!(
+            query_reserve(deps.as_ref(), mock_env()).unwrap(),
+            ReserveResponse {
+                denom: "unat".to_string(),
+                reserve: Uint128::new(280),
+                total_supply: Uint128::new(280),
+                surplus: Uint128::zero(),
+            }
+        );
+
+        // coins sent without Deposit back no tokens
+        deps.querier
+            .update_balance(mock_env().contract.address, coins(300, "unat"));
+        let res = query_reserve(deps.as_ref(), mock_env()).unwrap();
+        assert_eq!(res.surplus, Uint128::new(20));
+    }
+
+    #[test]
+    fn lockups() {
         let mut deps = mock_dependencies_with_balance(&[]);
         let owner = String::from("owner");
+        let team = String::from("team");
         let spender = String::from("spender");
-        let recipient = String::from("recipient");
+        do_instantiate(deps.as_mut(), &owner, Uint128::new(1000));
+        let start = mock_env().block.height;
+        let schedule = VestingSchedule::Height {
+            start,
+            cliff: start + 10,
+            end: start + 100,
+        };
 
-        let init_balance = Uint128::new(1000000000);
-        do_instantiate(deps.as_mut(), &owner, init_balance);
+        // only the owner can lock tokens, on a valid schedule
+        let msg = ExecuteMsg::CreateLockup {
+            address: team.clone(),
+            amount: Uint128::new(500),
+            schedule: schedule.clone(),
+        };
+        let err = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&team, &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        let bad = ExecuteMsg::CreateLockup {
+            address: team.clone(),
+            amount: Uint128::new(500),
+            schedule: VestingSchedule::Height {
+                start,
+                cliff: start + 200,
+                end: start + 100,
+            },
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), bad).unwrap_err();
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(&owner, &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::LockupExists {});
 
-        // approve
-        let allow1 = Uint128::new(1000000);
-        let msg = ExecuteMsg::Approve {
+        let spendable = |deps: Deps, env: Env| {
+            query_spendable_balance(deps, env, team.clone())
+                .unwrap()
+                .balance
+        };
+        assert_eq!(
+            query_balance(deps.as_ref(), team.clone()).unwrap().balance,
+            Uint128::new(500)
+        );
+        assert_eq!(spendable(deps.as_ref(), mock_env()), Uint128::zero());
+
+        // nothing moves before the cliff
+        let mut env = mock_env();
+        env.block.height = start + 9;
+        let transfer = |amount: u128| ExecuteMsg::Transfer {
+            recipient: owner.clone(),
+            amount: Uint128::new(amount),
+            on_receive_error: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&team, &[]),
+            transfer(1),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TokensLocked {
+                spendable: Uint128::zero()
+            }
+        );
+
+        // the cliff releases what accrued since start, spenders only get the released part
+        env.block.height = start + 10;
+        assert_eq!(
+            query_vesting(deps.as_ref(), env.clone(), team.clone()).unwrap(),
+            VestingResponse {
+                amount: Uint128::new(500),
+                schedule: Some(schedule),
+                locked: Uint128::new(450),
+            }
+        );
+        let msg = ExecuteMsg::IncreaseAllowance {
             spender: spender.clone(),
-            amount: allow1,
-            current_allowance: Uint128::zero(),
+            amount: Uint128::new(200),
+            expires: None,
         };
-        let info = mock_info(owner.as_ref(), &[]);
-        let env = mock_env();
-        let res = execute(deps.as_mut(), env, info, msg).unwrap();
-        assert_eq!(res.events[0].ty, "Approval");
-        let allowance = query_allowance(deps.as_ref(), owner.clone(), spender.clone()).unwrap();
-        assert_eq!(allow1, allowance.allowance);
+        execute(deps.as_mut(), env.clone(), mock_info(&team, &[]), msg).unwrap();
+        let transfer_from = |amount: u128| ExecuteMsg::TransferFrom {
+            owner: team.clone(),
+            recipient: spender.clone(),
+            amount: Uint128::new(amount),
+            on_receive_error: None,
+        };
+        let err = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&spender, &[]),
+            transfer_from(51),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ContractError::TokensLocked {
+                spendable: Uint128::new(50)
+            }
+        );
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(&spender, &[]),
+            transfer_from(50),
+        )
+        .unwrap();
+        assert_eq!(spendable(deps.as_ref(), env.clone()), Uint128::zero());
 
-        // transfer_from
-        let amount = Uint128::new(500000);
-        let msg = ExecuteMsg::TransferFrom {
-            owner: owner.clone(),
-            recipient: recipient.clone(),
-            amount: amount,
+        // everything is released at the end
+        env.block.height = start + 100;
+        assert_eq!(spendable(deps.as_ref(), env.clone()), Uint128::new(450));
+        let msg = ExecuteMsg::Burn {
+            amount: Uint128::new(450),
         };
-        let info = mock_info(spender.as_ref(), &[]);
-        let env = mock_env();
-        let res = execute(deps.as_mut(), env, info, msg).unwrap();
-        assert_eq!(res.events[0].ty, "Transfer");
-        let res = query_balance(deps.as_ref(), owner.clone()).unwrap();
-        assert_eq!(res.balance, init_balance - amount);
+        execute(deps.as_mut(), env, mock_info(&team, &[]), msg).unwrap();
+        assert_eq!(
+            query_balance(deps.as_ref(), team.clone()).unwrap().balance,
+            Uint128::zero()
+        );
     }
 }
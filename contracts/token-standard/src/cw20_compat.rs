@@ -0,0 +1,410 @@
+//! CW20 wire compatibility.
+//!
+//! Answers the standard cw20 `ExecuteMsg`/`QueryMsg` against the same storage as the
+//! native messages. Build with the `cw20-compat` feature to export entry points which take
+//! either message set, so the native-only messages stay reachable.
+//!
+//! Where both sets use the same name:
+//! - `transfer` and `transfer_from` always run the native handlers, so the receiver policy,
+//!   the Receive hook and its reply handling apply whichever way the message is written.
+//! - `send` and `send_from` are cw20's and dispatch the cw20 Receive hook. Contract
+//!   recipients must still pass the token's receiver policy. A `send` giving
+//!   `on_receive_error` is the native Send.
+//! - `all_accounts` is cw20's address list. The native query with balances and the next
+//!   page cursor is `all_account_balances`.
+//! - the other shared names take the same fields and answer the same JSON in both sets.
+
+#[cfg(all(feature = "cw20-compat", not(feature = "library")))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
+};
+use cw20::{
+    AllAccountsResponse, AllAllowancesResponse, AllowanceInfo, AllowanceResponse, BalanceResponse,
+    Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse, TokenInfoResponse,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::{
+    self, exec, query_all_accounts, query_all_allowances, query_allowance, query_balance,
+    query_download_logo, query_info, query_marketing_info, query_minter,
+};
+use crate::error::ContractError;
+use crate::event::transfer_event;
+use crate::msg::{ExecuteMsg, QueryMsg};
+
+/// Either message set. A message both sets can parse is read as cw20, see the module doc
+/// for how each shared name is handled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum CompatExecuteMsg {
+    Cw20(Cw20ExecuteMsg),
+    Native(ExecuteMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(untagged)]
+pub enum CompatQueryMsg {
+    Cw20(Cw20QueryMsg),
+    Renamed(RenamedQueryMsg),
+    Native(QueryMsg),
+}
+
+/// Native queries whose name cw20 takes, under the name they have in compat builds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum RenamedQueryMsg {
+    /// QueryMsg::AllAccounts
+    AllAccountBalances {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[cfg_attr(all(feature = "cw20-compat", not(feature = "library")), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CompatExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        CompatExecuteMsg::Cw20(msg) => execute_cw20(deps, env, info, msg),
+        CompatExecuteMsg::Native(msg) => contract::execute(deps, env, info, msg),
+    }
+}
+
+pub fn execute_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: Cw20ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        // the native handlers, which follow the receiver policy for contract recipients
+        Cw20ExecuteMsg::Transfer { recipient, amount } => {
+            exec::transfer(deps, env, info, recipient, amount, None)
+        }
+        Cw20ExecuteMsg::TransferFrom {
+            owner,
+            recipient,
+            amount,
+        } => exec::transfer_from(deps, env, info, owner, recipient, amount, None),
+        Cw20ExecuteMsg::Send {
+            contract,
+            amount,
+            msg,
+        } => send(deps, env, info, None, contract, amount, msg),
+        Cw20ExecuteMsg::SendFrom {
+            owner,
+            contract,
+            amount,
+            msg,
+        } => send(deps, env, info, Some(owner), contract, amount, msg),
+        Cw20ExecuteMsg::Burn { amount } => exec::burn(deps, env, info, amount),
+        Cw20ExecuteMsg::BurnFrom { owner, amount } => {
+            exec::burn_from(deps, env, info, owner, amount)
+        }
+        Cw20ExecuteMsg::IncreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => exec::increase_allowance(deps, env, info, spender, amount, expires),
+        Cw20ExecuteMsg::DecreaseAllowance {
+            spender,
+            amount,
+            expires,
+        } => exec::decrease_allowance(deps, env, info, spender, amount, expires),
+        Cw20ExecuteMsg::Mint { recipient, amount } => {
+            exec::mint(deps, env, info, recipient, amount)
+        }
+        Cw20ExecuteMsg::UpdateMinter { new_minter } => {
+            exec::update_minter(deps, env, info, new_minter)
+        }
+        Cw20ExecuteMsg::UpdateMarketing {
+            project,
+            description,
+            marketing,
+        } => exec::update_marketing(deps, env, info, project, description, marketing),
+        Cw20ExecuteMsg::UploadLogo(logo) => exec::upload_logo(deps, env, info, logo),
+    }
+}
+
+// moves tokens to contract like cw20 Send does, once it passes the receiver policy.
+// spends the sender's allowance when an owner is given.
+fn _move(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    owner: Option<String>,
+    contract: &str,
+    amount: Uint128,
+    msg: &Binary,
+) -> Result<Response, ContractError> {
+    exec::assert_not_paused(deps.storage)?;
+    if amount == Uint128::zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let rcpt_addr = deps.api.addr_validate(contract)?;
+    let owner_addr = match owner {
+        Some(owner) => {
+            let owner_addr = deps.api.addr_validate(&owner)?;
+            exec::_deduct_allowance(deps.storage, &env.block, &owner_addr, &info.sender, amount)?;
+            owner_addr
+        }
+        None => info.sender.clone(),
+    };
+    exec::assert_not_frozen(deps.storage, &[&owner_addr, &info.sender, &rcpt_addr])?;
+    exec::check_cw20_receiver(
+        deps.as_ref(),
+        env,
+        &info.sender,
+        &owner_addr,
+        contract,
+        amount,
+        msg,
+    )?;
+    exec::_transfer_balance(deps.storage, &env.block, &owner_addr, &rcpt_addr, amount)?;
+
+    Ok(Response::new().add_event(transfer_event(owner_addr.as_ref(), contract, amount)))
+}
+
+fn send(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    owner: Option<String>,
+    contract: String,
+    amount: Uint128,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    let res = _move(deps, &env, &info, owner, &contract, amount, &msg)?;
+
+    // cw20 reports the message sender (operator), not the owner, to the receiver
+    let receive = Cw20ReceiveMsg {
+        sender: info.sender.into(),
+        amount,
+        msg,
+    };
+    Ok(res.add_message(receive.into_cosmos_msg(contract)?))
+}
+
+#[cfg_attr(all(feature = "cw20-compat", not(feature = "library")), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: CompatQueryMsg) -> StdResult<Binary> {
+    match msg {
+        CompatQueryMsg::Cw20(msg) => query_cw20(deps, env, msg),
+        CompatQueryMsg::Renamed(RenamedQueryMsg::AllAccountBalances { start_after, limit }) => {
+            to_binary(&query_all_accounts(deps, start_after, limit)?)
+        }
+        CompatQueryMsg::Native(msg) => contract::query(deps, env, msg),
+    }
+}
+
+pub fn query_cw20(deps: Deps, _env: Env, msg: Cw20QueryMsg) -> StdResult<Binary> {
+    match msg {
+        Cw20QueryMsg::Balance { address } => {
+            let res = query_balance(deps, address)?;
+            to_binary(&BalanceResponse {
+                balance: res.balance,
+            })
+        }
+        Cw20QueryMsg::TokenInfo {} => {
+            let res = query_info(deps)?;
+            to_binary(&TokenInfoResponse {
+                name: res.name,
+                symbol: res.symbol,
+                decimals: res.decimal,
+                total_supply: res.total_supply,
+            })
+        }
+        Cw20QueryMsg::Minter {} => {
+            let res = query_minter(deps)?.map(|m| MinterResponse {
+                minter: m.minter,
+                cap: m.cap,
+            });
+            to_binary(&res)
+        }
+        Cw20QueryMsg::Allowance { owner, spender } => {
+            let res = query_allowance(deps, owner, spender)?;
+            to_binary(&AllowanceResponse {
+                allowance: res.allowance,
+                expires: res.expires,
+            })
+        }
+        Cw20QueryMsg::AllAllowances {
+            owner,
+            start_after,
+            limit,
+        } => {
+            let res = query_all_allowances(deps, owner, start_after, limit)?;
+            to_binary(&AllAllowancesResponse {
+                allowances: res
+                    .allowances
+                    .into_iter()
+                    .map(|a| AllowanceInfo {
+                        spender: a.spender,
+                        allowance: a.allowance,
+                        expires: a.expires,
+                    })
+                    .collect(),
+            })
+        }
+        Cw20QueryMsg::AllAccounts { start_after, limit } => {
+            let res = query_all_accounts(deps, start_after, limit)?;
+            to_binary(&AllAccountsResponse {
+                accounts: res.accounts.into_iter().map(|a| a.address).collect(),
+            })
+        }
+        Cw20QueryMsg::MarketingInfo {} => to_binary(&query_marketing_info(deps)?),
+        Cw20QueryMsg::DownloadLogo {} => to_binary(&query_download_logo(deps)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use cosmwasm_std::testing::{mock_dependencies_with_balance, mock_env, mock_info};
+    use cosmwasm_std::{from_binary, from_slice, CosmosMsg, WasmMsg};
+
+    use crate::contract::instantiate;
+    use crate::contract::tests::{instantiate_msg, mock_contracts};
+    use crate::msg::{self, StatusResponse};
+
+    #[test]
+    fn cw20_messages() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let contract = String::from("contract");
+
+        let msg = instantiate_msg(Uint128::new(1000));
+        instantiate(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+
+        let res = query_cw20(deps.as_ref(), mock_env(), Cw20QueryMsg::TokenInfo {}).unwrap();
+        let info: TokenInfoResponse = from_binary(&res).unwrap();
+        assert_eq!(info.decimals, 8);
+        assert_eq!(info.total_supply, Uint128::new(1000));
+
+        // send dispatches the cw20 receive hook with the payload
+        let payload = Binary::from(b"{\"deposit\":{}}".as_slice());
+        let msg = Cw20ExecuteMsg::Send {
+            contract: contract.clone(),
+            amount: Uint128::new(100),
+            msg: payload.clone(),
+        };
+        let res = execute_cw20(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let expected = Cw20ReceiveMsg {
+            sender: owner.clone(),
+            amount: Uint128::new(100),
+            msg: payload,
+        };
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: contract.clone(),
+                msg: expected.into_binary().unwrap(),
+                funds: vec![],
+            })
+        );
+
+        let msg = Cw20QueryMsg::Balance { address: contract };
+        let res = query_cw20(deps.as_ref(), mock_env(), msg).unwrap();
+        let balance: BalanceResponse = from_binary(&res).unwrap();
+        assert_eq!(balance.balance, Uint128::new(100));
+    }
+
+    #[test]
+    fn native_messages() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let recipient = String::from("recipient");
+        let msg = instantiate_msg(Uint128::new(1000));
+        instantiate(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+
+        // the wire format decides which message set handles a message
+        let msg: CompatExecuteMsg = from_slice(br#"{"pause":{}}"#).unwrap();
+        assert_eq!(msg, CompatExecuteMsg::Native(ExecuteMsg::Pause {}));
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let msg: CompatQueryMsg = from_slice(br#"{"status":{}}"#).unwrap();
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let status: StatusResponse = from_binary(&res).unwrap();
+        assert!(status.paused);
+
+        // messages valid in both sets are cw20, and the native pause still applies to them
+        let msg: CompatExecuteMsg =
+            from_slice(br#"{"transfer":{"recipient":"recipient","amount":"100"}}"#).unwrap();
+        assert_eq!(
+            msg,
+            CompatExecuteMsg::Cw20(Cw20ExecuteMsg::Transfer {
+                recipient,
+                amount: Uint128::new(100),
+            })
+        );
+        let err = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Paused {});
+    }
+
+    #[test]
+    fn shared_names() {
+        let mut deps = mock_dependencies_with_balance(&[]);
+        let owner = String::from("owner");
+        let receiver = String::from("receiver");
+        let plain = String::from("plain");
+        mock_contracts(&mut deps, &[&receiver], &[&plain]);
+        let msg = instantiate_msg(Uint128::new(1000));
+        instantiate(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+
+        // transfer gets the native receiver check and Receive hook however it is written
+        let msg: CompatExecuteMsg =
+            from_slice(br#"{"transfer":{"recipient":"receiver","amount":"100"}}"#).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        assert_eq!(
+            res.messages[0].msg,
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: receiver.clone(),
+                msg: to_binary(&ExecuteMsg::Receive {
+                    token: mock_env().contract.address.to_string(),
+                    from: owner.clone(),
+                    operator: owner.clone(),
+                    amount: Uint128::new(100),
+                    msg: None,
+                })
+                .unwrap(),
+                funds: vec![],
+            })
+        );
+        let msg: CompatExecuteMsg =
+            from_slice(br#"{"transfer":{"recipient":"plain","amount":"100"}}"#).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap_err();
+
+        // so does cw20 send, which then dispatches the cw20 hook
+        let msg: CompatExecuteMsg =
+            from_slice(br#"{"send":{"contract":"plain","amount":"100","msg":""}}"#).unwrap();
+        execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap_err();
+        let msg: CompatExecuteMsg =
+            from_slice(br#"{"send":{"contract":"receiver","amount":"100","msg":""}}"#).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), mock_info(&owner, &[]), msg).unwrap();
+        let expected = Cw20ReceiveMsg {
+            sender: owner.clone(),
+            amount: Uint128::new(100),
+            msg: Binary::default(),
+        };
+        assert_eq!(
+            res.messages[0].msg,
+            expected.into_cosmos_msg(receiver.clone()).unwrap()
+        );
+
+        // all_accounts is cw20's, the native query is renamed
+        let msg: CompatQueryMsg = from_slice(br#"{"all_accounts":{}}"#).unwrap();
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let accounts: AllAccountsResponse = from_binary(&res).unwrap();
+        assert_eq!(accounts.accounts, vec![owner.clone(), receiver.clone()]);
+        let msg: CompatQueryMsg = from_slice(br#"{"all_account_balances":{"limit":1}}"#).unwrap();
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let accounts: msg::AllAccountsResponse = from_binary(&res).unwrap();
+        assert_eq!(accounts.accounts[0].balance, Uint128::new(800));
+        assert_eq!(accounts.next_start_after, Some(owner));
+    }
+}
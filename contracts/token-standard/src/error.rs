@@ -1,4 +1,5 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
+use cw_utils::PaymentError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -6,6 +7,9 @@ pub enum ContractError {
     #[error("{0}")]
     Std(#[from] StdError),
 
+    #[error("{0}")]
+    Payment(#[from] PaymentError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
@@ -21,6 +25,102 @@ pub enum ContractError {
     #[error("No allowance for this account")]
     NoAllowance {},
 
+    #[error("Allowance is expired")]
+    Expired {},
+
+    #[error("Invalid expiration value")]
+    InvalidExpiration {},
+
     #[error("Recipient non-transferable")]
     NonTransferable {},
+
+    #[error("Recipient is not a contract")]
+    NotContract {},
+
+    #[error("Receiver {contract} rejected the transfer: {reason}")]
+    ReceiverRejected { contract: String, reason: String },
+
+    #[error("Account {address} is frozen")]
+    AccountFrozen { address: String },
+
+    #[error("Account {address} is not allowlisted")]
+    NotAllowlisted { address: String },
+
+    #[error("Invalid signature")]
+    InvalidSignature {},
+
+    #[error("Public key does not belong to the owner")]
+    PubkeyMismatch {},
+
+    #[error("Signed message is expired")]
+    SignatureExpired {},
+
+    #[error("Only {relayer} can relay this message")]
+    WrongRelayer { relayer: String },
+
+    #[error("Invalid nonce, expected {expected}")]
+    InvalidNonce { expected: u64 },
+
+    #[error("Token does not wrap a native coin")]
+    NotWrappedNative {},
+
+    #[error("Initial supply of {expected} must be sent along")]
+    InitialSupplyNotFunded { expected: Uint128 },
+
+    #[error("Wrapped native tokens cannot be burned, use Withdraw")]
+    WrappedNativeBurn {},
+
+    #[error("Account already has tokens locked")]
+    LockupExists {},
+
+    #[error("Cannot move locked tokens, spendable balance is {spendable}")]
+    TokensLocked { spendable: Uint128 },
+
+    #[error("Token is paused")]
+    Paused {},
+
+    #[error("No pending ownership transfer")]
+    NoPendingOwner {},
+
+    #[error("Ownership transfer expired")]
+    OwnershipTransferExpired {},
+
+    #[error("Duplicate initial balance addresses")]
+    DuplicateInitialBalanceAddresses {},
+
+    #[error("No transfers in batch")]
+    EmptyBatch {},
+
+    #[error("Invalid transfer at index {index}: {error}")]
+    InvalidBatchLeg {
+        index: usize,
+        error: Box<ContractError>,
+    },
+
+    #[error("Minting cannot exceed the cap")]
+    CannotExceedCap {},
+
+    #[error("Invalid xml preamble for SVG")]
+    InvalidXmlPreamble {},
+
+    #[error("Invalid png header")]
+    InvalidPngHeader {},
+
+    #[error("Logo binary data exceeds 5KB limit")]
+    LogoTooBig {},
+
+    #[error("Cannot migrate from different contract type: {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from unsupported version: {previous_version}")]
+    CannotMigrateVersion { previous_version: String },
+
+    #[error("Semver parsing error: {0}")]
+    SemVer(String),
+}
+
+impl From<semver::Error> for ContractError {
+    fn from(err: semver::Error) -> Self {
+        Self::SemVer(err.to_string())
+    }
 }
@@ -1,4 +1,5 @@
 use cosmwasm_std::{Event, Uint128};
+use cw_utils::Expiration;
 
 pub fn transfer_event(owner: &str, recipient: &str, amount: Uint128) -> Event {
     Event::new("Transfer")
@@ -19,3 +20,69 @@ pub fn approval_event(
         .add_attribute("old_amount", old_amount.to_string())
         .add_attribute("new_amount", new_amount.to_string())
 }
+
+pub fn mint_event(minter: &str, recipient: &str, amount: Uint128) -> Event {
+    Event::new("Mint")
+        .add_attribute("minter", minter.to_string())
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+pub fn deposit_event(owner: &str, amount: Uint128) -> Event {
+    Event::new("Deposit")
+        .add_attribute("owner", owner.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+pub fn withdraw_event(owner: &str, amount: Uint128) -> Event {
+    Event::new("Withdraw")
+        .add_attribute("owner", owner.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+pub fn burn_event(owner: &str, amount: Uint128) -> Event {
+    Event::new("Burn")
+        .add_attribute("owner", owner.to_string())
+        .add_attribute("amount", amount.to_string())
+}
+
+pub fn transfer_reverted_event(
+    owner: &str,
+    recipient: &str,
+    amount: Uint128,
+    reason: &str,
+) -> Event {
+    Event::new("TransferReverted")
+        .add_attribute("owner", owner.to_string())
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("reason", reason.to_string())
+}
+
+pub fn ownership_transfer_started_event(
+    owner: &str,
+    pending_owner: &str,
+    expiry: Expiration,
+) -> Event {
+    Event::new("OwnershipTransferStarted")
+        .add_attribute("owner", owner.to_string())
+        .add_attribute("pending_owner", pending_owner.to_string())
+        .add_attribute("expiry", expiry.to_string())
+}
+
+pub fn ownership_transferred_event(previous_owner: &str, new_owner: &str) -> Event {
+    Event::new("OwnershipTransferred")
+        .add_attribute("previous_owner", previous_owner.to_string())
+        .add_attribute("new_owner", new_owner.to_string())
+}
+
+pub fn ownership_renounced_event(previous_owner: &str) -> Event {
+    Event::new("OwnershipRenounced").add_attribute("previous_owner", previous_owner.to_string())
+}
+
+pub fn delegate_changed_event(delegator: &str, from_delegate: &str, to_delegate: &str) -> Event {
+    Event::new("DelegateChanged")
+        .add_attribute("delegator", delegator.to_string())
+        .add_attribute("from_delegate", from_delegate.to_string())
+        .add_attribute("to_delegate", to_delegate.to_string())
+}
@@ -1,7 +1,11 @@
 pub mod contract;
+pub mod cw20_compat;
 mod error;
 pub mod event;
+mod migrations;
 pub mod msg;
+pub mod ownership;
+pub mod signature;
 pub mod state;
 
 pub use crate::error::ContractError;
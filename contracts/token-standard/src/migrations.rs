@@ -0,0 +1,52 @@
+use cosmwasm_std::{Addr, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Item, Map};
+use serde::{Deserialize, Serialize};
+
+use crate::msg::AllowanceResponse;
+use crate::ownership::{initialize_owner, OWNERSHIP};
+use crate::state::{ALLOWANCES_SPENDER, COMPLIANCE, PAUSER, TOKEN_INFO, TOTAL_SUPPLY};
+
+// namespace ALLOWANCES_SPENDER was stored under before 0.2.0
+const LEGACY_ALLOWANCES_SPENDER: Map<(&Addr, &Addr), AllowanceResponse> =
+    Map::new("alloance_spender");
+
+// total supply was part of the token info before 0.2.0
+#[derive(Serialize, Deserialize)]
+struct LegacyTokenInfo {
+    total_supply: Uint128,
+}
+
+const LEGACY_TOKEN_INFO: Item<LegacyTokenInfo> = Item::new("token_info");
+
+/// Moves state written by 0.1.x into the 0.2.0 layout. 0.1.x had no roles, owner takes the
+/// owner, pauser and compliance roles. Balance history starts at height.
+pub fn v0_2_0(storage: &mut dyn Storage, height: u64, owner: Option<Addr>) -> StdResult<()> {
+    if TOTAL_SUPPLY.may_load(storage)?.is_none() {
+        let total_supply = LEGACY_TOKEN_INFO.load(storage)?.total_supply;
+        TOTAL_SUPPLY.save(storage, &total_supply, height)?;
+        // drops the legacy field
+        let info = TOKEN_INFO.load(storage)?;
+        TOKEN_INFO.save(storage, &info)?;
+    }
+
+    let entries = LEGACY_ALLOWANCES_SPENDER
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for ((spender, owner), allowance) in entries {
+        ALLOWANCES_SPENDER.save(storage, (&spender, &owner), &allowance)?;
+        LEGACY_ALLOWANCES_SPENDER.remove(storage, (&spender, &owner));
+    }
+
+    if let Some(owner) = &owner {
+        if !PAUSER.exists(storage) {
+            PAUSER.save(storage, owner)?;
+        }
+        if !COMPLIANCE.exists(storage) {
+            COMPLIANCE.save(storage, owner)?;
+        }
+    }
+    if !OWNERSHIP.exists(storage) {
+        initialize_owner(storage, owner)?;
+    }
+    Ok(())
+}
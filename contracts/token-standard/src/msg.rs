@@ -1,17 +1,34 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{StdError, StdResult, Uint128};
+use cosmwasm_std::{Binary, StdError, StdResult, Timestamp, Uint128};
+use cw20::{DownloadLogoResponse, Logo, MarketingInfoResponse};
+use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::ownership::Ownership;
+
 #[cw_serde]
 pub struct InstantiateMsg {
     pub name: String,
     pub symbol: String,
     pub decimals: u8,
-    pub initial_balances: Uint128,
+    pub initial_balances: InitialBalances,
+    pub mint: Option<MinterResponse>,
+    pub receiver_policy: Option<ReceiverPolicy>,
+    pub marketing: Option<InstantiateMarketingInfo>,
+    /// Turns on allowlist mode: only these accounts, and those added later, can hold tokens.
+    /// Initial balances must go to allowlisted accounts.
+    pub allowlist: Option<Vec<String>>,
+    /// Makes the token a 1:1 wrapper of this bank denom: Deposit mints, Withdraw burns. The
+    /// initial supply must be sent along, and neither a minter nor Burn are available.
+    pub native_denom: Option<String>,
 }
 
 impl InstantiateMsg {
+    pub fn get_cap(&self) -> Option<Uint128> {
+        self.mint.as_ref().and_then(|v| v.cap)
+    }
+
     pub fn validate(&self) -> StdResult<()> {
         if !is_valid_name(&self.name) {
             return Err(StdError::generic_err(
@@ -26,10 +43,59 @@ impl InstantiateMsg {
         if self.decimals > 18 {
             return Err(StdError::generic_err("Decimals must not exceed 18"));
         }
+        let initial_supply = self.initial_balances.total()?;
+        if let Some(cap) = self.get_cap() {
+            if initial_supply > cap {
+                return Err(StdError::generic_err("Initial supply greater than cap"));
+            }
+        }
+        if self.native_denom.is_some() && self.mint.is_some() {
+            return Err(StdError::generic_err(
+                "Wrapped native tokens cannot have a minter",
+            ));
+        }
         Ok(())
     }
 }
 
+#[cw_serde]
+pub struct InstantiateMarketingInfo {
+    pub project: Option<String>,
+    pub description: Option<String>,
+    /// The address which may update the marketing info and the logo
+    pub marketing: Option<String>,
+    pub logo: Option<Logo>,
+}
+
+/// Genesis distribution of the token
+#[cw_serde]
+#[serde(untagged)]
+pub enum InitialBalances {
+    /// Legacy form, the whole amount goes to the instantiator
+    Amount(Uint128),
+    Accounts(Vec<InitialBalance>),
+}
+
+impl InitialBalances {
+    /// Sum of all allocations, which becomes the initial total supply
+    pub fn total(&self) -> StdResult<Uint128> {
+        match self {
+            InitialBalances::Amount(amount) => Ok(*amount),
+            InitialBalances::Accounts(accounts) => {
+                accounts.iter().try_fold(Uint128::zero(), |total, account| {
+                    Ok(total.checked_add(account.amount)?)
+                })
+            }
+        }
+    }
+}
+
+#[cw_serde]
+pub struct InitialBalance {
+    pub address: String,
+    pub amount: Uint128,
+}
+
 fn is_valid_name(name: &str) -> bool {
     let bytes = name.as_bytes();
     if bytes.len() < 3 || bytes.len() > 12 {
@@ -51,26 +117,273 @@ fn is_valid_symbol(symbol: &str) -> bool {
     true
 }
 
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Owner to set when the stored token has none, i.e. it was instantiated before 0.2.0
+    pub owner: Option<String>,
+}
+
+/// How Transfer and TransferFrom treat smart contract recipients. Send always
+/// requires OnFTReceived and calls the Receive hook.
+#[cw_serde]
+#[derive(Default)]
+pub enum ReceiverPolicy {
+    /// Require OnFTReceived and call the Receive hook
+    #[default]
+    Strict,
+    /// Like Strict, but contracts without OnFTReceived receive tokens without the hook
+    Lenient,
+    /// Never query OnFTReceived nor call the Receive hook
+    Off,
+}
+
+impl ReceiverPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReceiverPolicy::Strict => "strict",
+            ReceiverPolicy::Lenient => "lenient",
+            ReceiverPolicy::Off => "off",
+        }
+    }
+}
+
+/// What to do when the Receive hook of a contract recipient fails
+#[cw_serde]
+#[derive(Default)]
+pub enum OnReceiveError {
+    /// Fail the whole transaction with ContractError::ReceiverRejected
+    #[default]
+    Fail,
+    /// Give the tokens back to the owner and emit a TransferReverted event
+    Revert,
+}
+
+#[cw_serde]
+pub struct TransferItem {
+    pub recipient: String,
+    pub amount: Uint128,
+}
+
 #[cw_serde]
 pub enum ExecuteMsg {
     Transfer {
         recipient: String,
         amount: Uint128,
+        on_receive_error: Option<OnReceiveError>,
     },
     TransferFrom {
         owner: String,
         recipient: String,
         amount: Uint128,
+        on_receive_error: Option<OnReceiveError>,
     },
     Approve {
         spender: String,
         amount: Uint128,
         current_allowance: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Allows spender to access an additional amount of tokens from the owner's (info.sender)
+    /// account. If expires is Some(), overwrites current allowance expiration with this one.
+    IncreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Lowers the spender's access of tokens from the owner's (info.sender) account by amount.
+    /// If expires is Some(), overwrites current allowance expiration with this one.
+    DecreaseAllowance {
+        spender: String,
+        amount: Uint128,
+        expires: Option<Expiration>,
+    },
+    /// Debits the sender once and credits every recipient. Fails as a whole on any invalid leg.
+    BatchTransfer {
+        transfers: Vec<TransferItem>,
+        on_receive_error: Option<OnReceiveError>,
+    },
+    /// Like BatchTransfer, spending the sender's allowance for the sum of all legs. The owner
+    /// itself needs an allowance too, as with TransferFrom.
+    BatchTransferFrom {
+        owner: String,
+        transfers: Vec<TransferItem>,
+        on_receive_error: Option<OnReceiveError>,
+    },
+    /// Transfers amount to a contract and triggers its Receive hook with msg
+    Send {
+        contract: String,
+        amount: Uint128,
+        msg: Binary,
+        on_receive_error: Option<OnReceiveError>,
     },
+    /// Hook dispatched to a contract recipient. token is this token contract, from is the
+    /// account the tokens came from and operator is the account which moved them (the spender
+    /// for TransferFrom). msg is the payload given to Send, if any.
     Receive {
-        sender: String,
+        token: String,
+        from: String,
+        operator: String,
+        amount: Uint128,
+        msg: Option<Binary>,
+    },
+    /// If authorized, creates amount new tokens and adds to the recipient balance.
+    Mint { recipient: String, amount: Uint128 },
+    /// The current minter may set a new minter. Setting None disables minting.
+    UpdateMinter { new_minter: Option<String> },
+    /// Only the owner can change how contract recipients are treated
+    UpdateReceiverPolicy { policy: ReceiverPolicy },
+    /// If authorized, updates marketing metadata. Setting a field to an empty string clears it.
+    UpdateMarketing {
+        /// A URL pointing to the project behind this token
+        project: Option<String>,
+        /// A longer description of the token and its utility
+        description: Option<String>,
+        /// The address which may update the marketing info and the logo
+        marketing: Option<String>,
+    },
+    /// If set as the marketing address, uploads a new logo: a URL or an embedded PNG/SVG
+    UploadLogo(Logo),
+    /// The owner starts handing ownership to new_owner, who has to accept it before expiry
+    TransferOwnership {
+        new_owner: String,
+        expiry: Option<Expiration>,
+    },
+    /// The pending owner accepts the ownership transfer
+    AcceptOwnership {},
+    /// The owner gives up ownership. Owner-only messages can no longer be run.
+    RenounceOwnership {},
+    /// The pauser stops transfers, approvals, minting and burning
+    Pause {},
+    /// The pauser resumes the token
+    Unpause {},
+    /// The owner hands the pauser role to another account. None removes the role.
+    UpdatePauser { new_pauser: Option<String> },
+    /// The compliance role stops address from sending, receiving and approving tokens
+    Freeze { address: String },
+    /// The compliance role lifts the freeze on address
+    Unfreeze { address: String },
+    /// The owner hands the compliance role to another account. None removes the role.
+    UpdateCompliance { new_compliance: Option<String> },
+    /// The compliance role allows address to hold tokens in allowlist mode
+    AddToAllowlist { address: String },
+    /// The compliance role removes address from the allowlist. Its balance is kept.
+    RemoveFromAllowlist { address: String },
+    /// The owner turns allowlist mode on or off
+    UpdateAllowlistMode { allowlist_only: bool },
+    /// Gives the sender's voting power, current and future balance, to delegatee.
+    /// Delegate to yourself to vote with your own balance.
+    Delegate { delegatee: String },
+    /// Sets the allowance of spender over owner's tokens to amount, authorized by owner's
+    /// signature of a PermitSignDoc instead of a transaction. Anyone can submit it.
+    Permit {
+        owner: String,
+        spender: String,
         amount: Uint128,
+        expires: Option<Expiration>,
+        nonce: u64,
+        /// owner's compressed secp256k1 public key
+        pubkey: Binary,
+        /// 64 byte signature of the sha256 hash of the PermitSignDoc JSON
+        signature: Binary,
     },
+    /// Transfers amount from from to recipient, authorized by from's signature of a
+    /// RelayedTransferSignDoc. The relayer submitting it receives fee from from's balance.
+    /// Contract recipients get the Receive hook like with Transfer.
+    RelayedTransfer {
+        from: String,
+        recipient: String,
+        amount: Uint128,
+        fee: Uint128,
+        nonce: u64,
+        /// the transfer cannot be relayed once this is expired
+        deadline: Expiration,
+        /// if set, only this account can relay the transfer and take the fee
+        relayer: Option<String>,
+        /// from's compressed secp256k1 public key
+        pubkey: Binary,
+        /// 64 byte signature of the sha256 hash of the RelayedTransferSignDoc JSON
+        signature: Binary,
+    },
+    /// Mints the native coins sent along 1:1 to the sender. Only for wrapped native tokens.
+    Deposit {},
+    /// Burns amount from the sender's balance and sends the same amount of native coins back.
+    /// Only for wrapped native tokens.
+    Withdraw { amount: Uint128 },
+    /// The owner moves amount of its tokens to address, locked until released by schedule.
+    /// Locked tokens count in the balance but cannot be transferred or burned.
+    CreateLockup {
+        address: String,
+        amount: Uint128,
+        schedule: VestingSchedule,
+    },
+    /// Burn is a base message to destroy tokens forever
+    Burn { amount: Uint128 },
+    /// Destroys tokens forever from owner's balance, consuming the sender's allowance
+    BurnFrom { owner: String, amount: Uint128 },
+}
+
+/// How locked tokens are released: nothing before cliff, then linearly from start so that
+/// everything is released at end. The cliff releases what accrued since start at once.
+#[cw_serde]
+pub enum VestingSchedule {
+    /// by block height
+    Height { start: u64, cliff: u64, end: u64 },
+    /// by block time
+    Time {
+        start: Timestamp,
+        cliff: Timestamp,
+        end: Timestamp,
+    },
+}
+
+impl VestingSchedule {
+    pub fn validate(&self) -> StdResult<()> {
+        let (start, cliff, end) = self.points();
+        if start > cliff || cliff > end || start == end {
+            return Err(StdError::generic_err(
+                "Vesting schedule must have start <= cliff <= end and start < end",
+            ));
+        }
+        Ok(())
+    }
+
+    /// start, cliff and end in blocks or nanoseconds
+    pub fn points(&self) -> (u64, u64, u64) {
+        match self {
+            VestingSchedule::Height { start, cliff, end } => (*start, *cliff, *end),
+            VestingSchedule::Time { start, cliff, end } => {
+                (start.nanos(), cliff.nanos(), end.nanos())
+            }
+        }
+    }
+}
+
+/// What the owner signs to grant a Permit. chain_id and contract keep the signature from being
+/// replayed on another chain or token.
+#[cw_serde]
+pub struct PermitSignDoc {
+    pub chain_id: String,
+    pub contract: String,
+    pub owner: String,
+    pub spender: String,
+    pub amount: Uint128,
+    pub expires: Option<Expiration>,
+    pub nonce: u64,
+}
+
+/// What the sender signs to have a RelayedTransfer submitted for it. Shares the nonce with
+/// PermitSignDoc.
+#[cw_serde]
+pub struct RelayedTransferSignDoc {
+    pub chain_id: String,
+    pub contract: String,
+    pub from: String,
+    pub recipient: String,
+    pub amount: Uint128,
+    pub fee: Uint128,
+    pub nonce: u64,
+    pub deadline: Expiration,
+    pub relayer: Option<String>,
 }
 
 #[cw_serde]
@@ -82,14 +395,93 @@ pub enum QueryMsg {
     TotalSupply {},
     #[returns(BalanceResponse)]
     Balance { owner: String },
+    /// Returns the balance of address at the beginning of the block at height
+    #[returns(BalanceResponse)]
+    BalanceAtHeight { address: String, height: u64 },
+    /// Returns the total supply at the beginning of the block at height
+    #[returns(TotalSupplyResponse)]
+    TotalSupplyAtHeight { height: u64 },
     #[returns(AllowanceResponse)]
     Allowance { owner: String, spender: String },
     #[returns(OnFTReceivedResponse)]
     OnFTReceived {
-        sender: String,
-        owner: String,
+        token: String,
+        from: String,
+        operator: String,
         amount: Uint128,
+        msg: Option<Binary>,
+    },
+    /// Returns who can mint and the hard cap on maximum tokens after minting.
+    #[returns(Option<MinterResponse>)]
+    Minter {},
+    /// Returns all allowances this owner has approved. Supports pagination.
+    #[returns(AllAllowancesResponse)]
+    AllAllowances {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all allowances this spender has been granted. Supports pagination.
+    #[returns(AllSpenderAllowancesResponse)]
+    AllSpenderAllowances {
+        spender: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns all accounts holding a balance, in lexicographic order. Supports pagination.
+    #[returns(AllAccountsResponse)]
+    AllAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the allowlisted accounts in lexicographic order. Supports pagination.
+    #[returns(AllowlistResponse)]
+    Allowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
     },
+    /// Returns the frozen accounts in lexicographic order. Supports pagination.
+    #[returns(FrozenAccountsResponse)]
+    FrozenAccounts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Returns the voting power delegated to address, at the beginning of the block at height
+    /// if given
+    #[returns(VotingPowerResponse)]
+    VotingPower {
+        address: String,
+        height: Option<u64>,
+    },
+    /// Returns the account address delegates its voting power to
+    #[returns(DelegatesResponse)]
+    Delegates { address: String },
+    /// Returns the nonce the next message signed by owner must use
+    #[returns(NonceResponse)]
+    Nonce { owner: String },
+    /// Returns the native coins held by a wrapped native token next to its total supply.
+    /// The reserve is never below the supply. It is above it when coins are sent to the
+    /// contract directly instead of through Deposit.
+    #[returns(ReserveResponse)]
+    Reserve {},
+    /// Returns the lockup of address and how much of it is still locked
+    #[returns(VestingResponse)]
+    Vesting { address: String },
+    /// Returns the part of the balance of address that can be moved now
+    #[returns(BalanceResponse)]
+    SpendableBalance { address: String },
+    /// Returns whether the token is paused and who can pause it
+    #[returns(StatusResponse)]
+    Status {},
+    /// Returns the owner and the pending ownership transfer, if any
+    #[returns(Ownership)]
+    Ownership {},
+    /// Returns the marketing metadata. Fields never set are None.
+    #[returns(MarketingInfoResponse)]
+    MarketingInfo {},
+    /// Returns the embedded logo with its mime type. Fails if the logo is unset or a URL.
+    #[returns(DownloadLogoResponse)]
+    DownloadLogo {},
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
@@ -98,6 +490,8 @@ pub struct InfoResponse {
     pub symbol: String,
     pub decimal: u8,
     pub total_supply: Uint128,
+    pub receiver_policy: ReceiverPolicy,
+    pub allowlist_only: bool,
 }
 
 // #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
@@ -115,6 +509,61 @@ pub struct BalanceResponse {
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct AllowanceResponse {
     pub allowance: Uint128,
+    // allowances stored before 0.2.0 have no expiry
+    #[serde(default)]
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct AllowlistResponse {
+    pub accounts: Vec<String>,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct FrozenAccountsResponse {
+    pub accounts: Vec<String>,
+}
+
+#[cw_serde]
+pub struct VotingPowerResponse {
+    pub power: Uint128,
+}
+
+#[cw_serde]
+pub struct DelegatesResponse {
+    pub delegate: Option<String>,
+}
+
+#[cw_serde]
+pub struct NonceResponse {
+    pub nonce: u64,
+}
+
+#[cw_serde]
+pub struct ReserveResponse {
+    pub denom: String,
+    /// the contract's bank balance of denom
+    pub reserve: Uint128,
+    pub total_supply: Uint128,
+    /// the part of reserve not backing any token
+    pub surplus: Uint128,
+}
+
+#[cw_serde]
+pub struct VestingResponse {
+    /// the amount put in the lockup, zero if there is none
+    pub amount: Uint128,
+    pub schedule: Option<VestingSchedule>,
+    /// the part of amount not released yet
+    pub locked: Uint128,
+}
+
+#[cw_serde]
+pub struct StatusResponse {
+    pub paused: bool,
+    pub pauser: Option<String>,
 }
 
 #[cw_serde]
@@ -122,3 +571,51 @@ pub struct OnFTReceivedResponse {
     // true if this contract can receive ft
     pub enable: bool,
 }
+
+#[cw_serde]
+pub struct MinterResponse {
+    pub minter: String,
+    /// cap is a hard cap on total supply that can be achieved by minting.
+    pub cap: Option<Uint128>,
+}
+
+#[cw_serde]
+pub struct AllowanceInfo {
+    pub spender: String,
+    pub allowance: Uint128,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct AllAllowancesResponse {
+    pub allowances: Vec<AllowanceInfo>,
+}
+
+#[cw_serde]
+pub struct SpenderAllowanceInfo {
+    pub owner: String,
+    pub allowance: Uint128,
+    pub expires: Expiration,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct AllSpenderAllowancesResponse {
+    pub allowances: Vec<SpenderAllowanceInfo>,
+}
+
+#[cw_serde]
+pub struct AccountBalance {
+    pub address: String,
+    pub balance: Uint128,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub struct AllAccountsResponse {
+    pub accounts: Vec<AccountBalance>,
+    // pass as start_after to fetch the next page, None if this is the last one.
+    // accounts with a zero balance are left out, so a page can be short before the last one.
+    pub next_start_after: Option<String>,
+}
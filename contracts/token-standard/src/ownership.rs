@@ -0,0 +1,125 @@
+//! Two-step ownership of the token.
+//!
+//! The owner authorizes privileged messages. A transfer only completes once the new
+//! owner accepts it, so ownership cannot be handed to an address nobody controls.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, BlockInfo, DepsMut, Env, MessageInfo, Response, StdResult, Storage};
+use cw_storage_plus::Item;
+use cw_utils::Expiration;
+
+use crate::error::ContractError;
+use crate::event::{
+    ownership_renounced_event, ownership_transfer_started_event, ownership_transferred_event,
+};
+
+#[cw_serde]
+#[derive(Default)]
+pub struct Ownership {
+    /// The account allowed to run privileged messages. None once ownership is renounced.
+    pub owner: Option<Addr>,
+    /// The account a transfer was started to, until it accepts
+    pub pending_owner: Option<Addr>,
+    /// When the pending transfer can no longer be accepted
+    pub pending_expiry: Option<Expiration>,
+}
+
+pub const OWNERSHIP: Item<Ownership> = Item::new("ownership");
+
+pub fn initialize_owner(storage: &mut dyn Storage, owner: Option<Addr>) -> StdResult<()> {
+    OWNERSHIP.save(
+        storage,
+        &Ownership {
+            owner,
+            ..Ownership::default()
+        },
+    )
+}
+
+pub fn get_ownership(storage: &dyn Storage) -> StdResult<Ownership> {
+    Ok(OWNERSHIP.may_load(storage)?.unwrap_or_default())
+}
+
+/// Fails with Unauthorized unless sender is the current owner
+pub fn assert_owner(storage: &dyn Storage, sender: &Addr) -> Result<Ownership, ContractError> {
+    let ownership = get_ownership(storage)?;
+    if ownership.owner.as_ref() != Some(sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(ownership)
+}
+
+/// Starts a transfer to new_owner, replacing any pending one
+pub fn transfer_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_owner: String,
+    expiry: Option<Expiration>,
+) -> Result<Response, ContractError> {
+    let mut ownership = assert_owner(deps.storage, &info.sender)?;
+    let new_owner = deps.api.addr_validate(&new_owner)?;
+    if let Some(expiry) = expiry {
+        if expiry.is_expired(&env.block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+    }
+
+    ownership.pending_owner = Some(new_owner.clone());
+    ownership.pending_expiry = expiry;
+    OWNERSHIP.save(deps.storage, &ownership)?;
+
+    Ok(Response::new().add_event(ownership_transfer_started_event(
+        info.sender.as_ref(),
+        new_owner.as_ref(),
+        expiry.unwrap_or_default(),
+    )))
+}
+
+/// Completes the pending transfer. Only the pending owner can accept, before the expiry.
+pub fn accept_ownership(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut ownership = get_ownership(deps.storage)?;
+    let pending_owner = ownership
+        .pending_owner
+        .as_ref()
+        .ok_or(ContractError::NoPendingOwner {})?;
+    if pending_owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if is_pending_expired(&ownership, &env.block) {
+        return Err(ContractError::OwnershipTransferExpired {});
+    }
+
+    let previous_owner = ownership.owner.take();
+    ownership.owner = ownership.pending_owner.take();
+    ownership.pending_expiry = None;
+    OWNERSHIP.save(deps.storage, &ownership)?;
+
+    Ok(Response::new().add_event(ownership_transferred_event(
+        previous_owner
+            .as_ref()
+            .map(Addr::as_str)
+            .unwrap_or_default(),
+        info.sender.as_ref(),
+    )))
+}
+
+/// Gives up ownership for good, dropping any pending transfer
+pub fn renounce_ownership(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    assert_owner(deps.storage, &info.sender)?;
+    OWNERSHIP.save(deps.storage, &Ownership::default())?;
+
+    Ok(Response::new().add_event(ownership_renounced_event(info.sender.as_ref())))
+}
+
+fn is_pending_expired(ownership: &Ownership, block: &BlockInfo) -> bool {
+    matches!(ownership.pending_expiry, Some(expiry) if expiry.is_expired(block))
+}
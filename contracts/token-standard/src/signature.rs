@@ -0,0 +1,64 @@
+//! Verification of messages signed off-chain by token holders.
+//!
+//! A sign doc is serialized to JSON with `cosmwasm_std::to_vec`, hashed with sha256 and
+//! signed with the holder's secp256k1 key. The public key must derive to the holder's
+//! address the way Cosmos SDK accounts do: `ripemd160(sha256(pubkey))`.
+
+use bech32::FromBase32;
+use cosmwasm_std::{to_vec, Addr, Binary, Deps, StdResult, Storage};
+use ripemd::Ripemd160;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::state::NONCES;
+
+/// Fails unless signature is a valid signature of sign_doc by the key of signer
+pub fn verify_signature<T: Serialize>(
+    deps: Deps,
+    signer: &Addr,
+    sign_doc: &T,
+    pubkey: &Binary,
+    signature: &Binary,
+) -> Result<(), ContractError> {
+    if pubkey_address_bytes(pubkey) != address_bytes(signer)? {
+        return Err(ContractError::PubkeyMismatch {});
+    }
+
+    let hash = Sha256::digest(to_vec(sign_doc)?);
+    let valid = deps
+        .api
+        .secp256k1_verify(&hash, signature, pubkey)
+        .map_err(|_| ContractError::InvalidSignature {})?;
+    if !valid {
+        return Err(ContractError::InvalidSignature {});
+    }
+    Ok(())
+}
+
+/// Checks nonce is the next one expected from owner and consumes it, so a signed message
+/// can only be used once
+pub fn use_nonce(storage: &mut dyn Storage, owner: &Addr, nonce: u64) -> Result<(), ContractError> {
+    let expected = NONCES.may_load(storage, owner)?.unwrap_or_default();
+    if nonce != expected {
+        return Err(ContractError::InvalidNonce { expected });
+    }
+    NONCES.save(storage, owner, &(expected + 1))?;
+    Ok(())
+}
+
+pub fn query_nonce(storage: &dyn Storage, owner: &Addr) -> StdResult<u64> {
+    Ok(NONCES.may_load(storage, owner)?.unwrap_or_default())
+}
+
+// the account bytes of a Cosmos SDK secp256k1 key
+fn pubkey_address_bytes(pubkey: &[u8]) -> Vec<u8> {
+    Ripemd160::digest(Sha256::digest(pubkey)).to_vec()
+}
+
+// the bytes behind a bech32 address
+fn address_bytes(addr: &Addr) -> Result<Vec<u8>, ContractError> {
+    let (_, data, _) =
+        bech32::decode(addr.as_str()).map_err(|_| ContractError::PubkeyMismatch {})?;
+    Vec::<u8>::from_base32(&data).map_err(|_| ContractError::PubkeyMismatch {})
+}
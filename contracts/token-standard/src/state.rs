@@ -1,10 +1,11 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, BlockInfo, Empty, Uint128};
+use cw20::{Logo, MarketingInfoResponse};
+use cw_storage_plus::{Item, Map, SnapshotItem, SnapshotMap, Strategy};
 
-use crate::msg::AllowanceResponse;
+use crate::msg::{AllowanceResponse, OnReceiveError, ReceiverPolicy, VestingSchedule};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -12,10 +13,101 @@ pub struct TokenInfo {
     pub name: String,
     pub symbol: String,
     pub decimals: u8,
-    pub total_supply: Uint128,
+    pub mint: Option<MinterData>,
+    // tokens instantiated before 0.2.0 keep the strict behaviour
+    #[serde(default)]
+    pub receiver_policy: ReceiverPolicy,
+    // only allowlisted accounts can be credited
+    #[serde(default)]
+    pub allowlist_only: bool,
+    // the bank denom wrapped 1:1, if any
+    #[serde(default)]
+    pub native_denom: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinterData {
+    pub minter: Addr,
+    /// cap is a hard cap on total supply that can be achieved by minting.
+    pub cap: Option<Uint128>,
+}
+
+impl TokenInfo {
+    pub fn get_cap(&self) -> Option<Uint128> {
+        self.mint.as_ref().and_then(|v| v.cap)
+    }
+}
+
+/// Tokens of an account released over time
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Lockup {
+    pub amount: Uint128,
+    pub schedule: VestingSchedule,
+}
+
+impl Lockup {
+    /// The part of amount not released at block
+    pub fn locked(&self, block: &BlockInfo) -> Uint128 {
+        let (start, cliff, end) = self.schedule.points();
+        let now = match self.schedule {
+            VestingSchedule::Height { .. } => block.height,
+            VestingSchedule::Time { .. } => block.time.nanos(),
+        };
+        if now < cliff {
+            self.amount
+        } else if now >= end {
+            Uint128::zero()
+        } else {
+            self.amount - self.amount.multiply_ratio(now - start, end - start)
+        }
+    }
+}
+
+/// A Receive hook waiting for its reply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingReceive {
+    pub owner: Addr,
+    pub recipient: Addr,
+    pub amount: Uint128,
+    pub on_error: OnReceiveError,
 }
 
 pub const TOKEN_INFO: Item<TokenInfo> = Item::new("token_info");
-pub const BALANCES: Map<&Addr, Uint128> = Map::new("balance");
+pub const PAUSER: Item<Addr> = Item::new("pauser");
+pub const PAUSED: Item<bool> = Item::new("paused");
+pub const COMPLIANCE: Item<Addr> = Item::new("compliance");
+pub const FROZEN: Map<&Addr, Empty> = Map::new("frozen");
+pub const ALLOWLIST: Map<&Addr, Empty> = Map::new("allowlist");
+pub const MARKETING_INFO: Item<MarketingInfoResponse> = Item::new("marketing_info");
+pub const LOGO: Item<Logo> = Item::new("logo");
+// balances and total supply keep their history, to be read at any past height
+pub const BALANCES: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "balance",
+    "balance__checkpoints",
+    "balance__changelog",
+    Strategy::EveryBlock,
+);
+/// Locked part of each balance, only spendable once released
+pub const LOCKUPS: Map<&Addr, Lockup> = Map::new("lockup");
+/// Next nonce expected in a message signed by each owner
+pub const NONCES: Map<&Addr, u64> = Map::new("nonce");
+/// The account each delegator gives its voting power to
+pub const DELEGATES: Map<&Addr, Addr> = Map::new("delegates");
+/// Sum of the balances delegated to each account
+pub const VOTING_POWER: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "voting_power",
+    "voting_power__checkpoints",
+    "voting_power__changelog",
+    Strategy::EveryBlock,
+);
+pub const TOTAL_SUPPLY: SnapshotItem<Uint128> = SnapshotItem::new(
+    "total_supply",
+    "total_supply__checkpoints",
+    "total_supply__changelog",
+    Strategy::EveryBlock,
+);
 pub const ALLOWANCES: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("allowance");
-pub const ALLOWANCES_SPENDER: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("alloance_spender");
+pub const ALLOWANCES_SPENDER: Map<(&Addr, &Addr), AllowanceResponse> =
+    Map::new("allowance_spender");
+pub const PENDING_RECEIVES: Map<u64, PendingReceive> = Map::new("pending_receive");
+pub const NEXT_REPLY_ID: Item<u64> = Item::new("next_reply_id");